@@ -0,0 +1,213 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bt_logger::log_warning;
+use md5::Md5;
+use reqwest::{
+    header::{HeaderValue, AUTHORIZATION},
+    Request, Url,
+};
+use sha2::{Digest, Sha256};
+
+use crate::oauth2::{OAuth2Config, OAuth2TokenProvider};
+
+const REDACTED: &str = "***";
+
+static CNONCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+///HttpAuth: Authentication applied by HttpClient to every request (see HttpClient::set_auth).
+/// Basic: Authorization: Basic base64(username:password)
+/// Bearer: Authorization: Bearer token
+/// Digest: HTTP Digest (RFC 7616). The request is sent without credentials and, when the server answers 401 with a
+///         WWW-Authenticate: Digest challenge, it is sent again once with the computed response.
+///         Supported algorithms: MD5, MD5-sess, SHA-256 and SHA-256-sess with qop "auth" or no qop.
+/// OAuth2: Bearer token obtained (and cached) by an OAuth2TokenProvider. On 401 the token is renewed and the request sent again once.
+/// Debug output never shows passwords or tokens.
+#[derive(Clone)]
+pub enum HttpAuth {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+    Digest { username: String, password: String },
+    OAuth2(OAuth2TokenProvider),
+}
+
+impl fmt::Debug for HttpAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpAuth::Basic { username, .. } => write!(f, "Basic {{ username: {:?}, password: {} }}", username, REDACTED),
+            HttpAuth::Bearer { .. } => write!(f, "Bearer {{ token: {} }}", REDACTED),
+            HttpAuth::Digest { username, .. } => write!(f, "Digest {{ username: {:?}, password: {} }}", username, REDACTED),
+            HttpAuth::OAuth2(provider) => write!(f, "OAuth2({:?})", provider),
+        }
+    }
+}
+
+impl HttpAuth {
+    pub fn basic(username: &str, password: &str) -> Self {
+        HttpAuth::Basic { username: username.to_owned(), password: password.to_owned() }
+    }
+
+    pub fn bearer(token: &str) -> Self {
+        HttpAuth::Bearer { token: token.to_owned() }
+    }
+
+    pub fn digest(username: &str, password: &str) -> Self {
+        HttpAuth::Digest { username: username.to_owned(), password: password.to_owned() }
+    }
+
+    ///OAuth2 authentication with a new token provider. Use HttpAuth::OAuth2(provider) to share a provider (and its cache) between clients.
+    pub fn oauth2(config: OAuth2Config) -> Self {
+        HttpAuth::OAuth2(OAuth2TokenProvider::new(config))
+    }
+
+    ///Adds the Authorization header for Basic and Bearer. Digest needs a challenge first and OAuth2 a token so nothing is added.
+    pub(crate) fn authorize(&self, request: &mut Request) {
+        match self {
+            HttpAuth::Basic { username, password } => {
+                set_authorization(request, &format!("Basic {}", STANDARD.encode(format!("{}:{}", username, password))))
+            }
+            HttpAuth::Bearer { token } => set_authorization(request, &format!("Bearer {}", token)),
+            HttpAuth::Digest { .. } | HttpAuth::OAuth2(_) => {}
+        }
+    }
+
+    ///Answers a WWW-Authenticate: Digest challenge. Returns None if this is not Digest auth or the challenge is not supported.
+    pub(crate) fn digest_response(&self, challenge: &str, request: &Request) -> Option<HeaderValue> {
+        let HttpAuth::Digest { username, password } = self else {
+            return None;
+        };
+        let params = parse_digest_challenge(challenge)?;
+        let realm = params.get("realm").cloned().unwrap_or_default();
+        let nonce = params.get("nonce")?.clone();
+        let algorithm = params.get("algorithm").cloned().unwrap_or_else(|| "MD5".to_owned());
+        let hash: fn(&str) -> String = match algorithm.to_uppercase().as_str() {
+            "MD5" | "MD5-SESS" => md5_hex,
+            "SHA-256" | "SHA-256-SESS" => sha256_hex,
+            _ => {
+                log_warning!("digest_response", "Unsupported Digest algorithm {}", algorithm);
+                return None;
+            }
+        };
+        let qop = match params.get("qop") {
+            Some(q) if q.split(',').any(|v| v.trim().eq_ignore_ascii_case("auth")) => Some("auth"),
+            Some(q) => {
+                log_warning!("digest_response", "Unsupported Digest qop {}", q);
+                return None;
+            }
+            None => None,
+        };
+
+        let uri = request_target(request.url());
+        let cnonce = new_cnonce();
+        let nc = "00000001";
+        let mut ha1 = hash(&format!("{}:{}:{}", username, realm, password));
+        if algorithm.to_uppercase().ends_with("-SESS") {
+            ha1 = hash(&format!("{}:{}:{}", ha1, nonce, cnonce));
+        }
+        let ha2 = hash(&format!("{}:{}", request.method().as_str(), uri));
+        let response = match qop {
+            Some(q) => hash(&format!("{}:{}:{}:{}:{}:{}", ha1, nonce, nc, cnonce, q, ha2)),
+            None => hash(&format!("{}:{}:{}", ha1, nonce, ha2)),
+        };
+
+        let mut value = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+            username, realm, nonce, uri, algorithm, response
+        );
+        if let Some(q) = qop {
+            value.push_str(&format!(", qop={}, nc={}, cnonce=\"{}\"", q, nc, cnonce));
+        }
+        if let Some(opaque) = params.get("opaque") {
+            value.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+        let mut hv = HeaderValue::from_str(&value).ok()?;
+        hv.set_sensitive(true);
+        Some(hv)
+    }
+}
+
+///Helper Function: Sets the Authorization header marked as sensitive.
+pub(crate) fn set_authorization(request: &mut Request, value: &str) {
+    match HeaderValue::from_str(value) {
+        Ok(mut v) => {
+            v.set_sensitive(true);
+            request.headers_mut().insert(AUTHORIZATION, v);
+        }
+        Err(_) => log_warning!("set_authorization", "Invalid characters in credentials. Authorization header not added"),
+    }
+}
+
+///Returns the URL as a string without the password (user:password@host becomes user:***@host). Use it in log messages.
+pub fn redact_url(url: &Url) -> String {
+    if url.password().is_none() {
+        return url.to_string();
+    }
+    let mut redacted = url.clone();
+    let _ = redacted.set_password(Some(REDACTED));
+    redacted.to_string()
+}
+
+///Helper Function: Parses the parameters of a Digest challenge. None if the challenge is not Digest.
+fn parse_digest_challenge(challenge: &str) -> Option<std::collections::HashMap<String, String>> {
+    let (scheme, rest) = challenge.trim().split_once(' ')?;
+    if !scheme.eq_ignore_ascii_case("digest") {
+        return None;
+    }
+
+    let mut params = std::collections::HashMap::new();
+    let mut chars = rest.chars().peekable();
+    loop {
+        while chars.peek().is_some_and(|c| *c == ',' || c.is_whitespace()) {
+            chars.next();
+        }
+        let key: String = chars.by_ref().take_while(|c| *c != '=').collect::<String>().trim().to_lowercase();
+        if key.is_empty() {
+            break;
+        }
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    _ => value.push(c),
+                }
+            }
+        } else {
+            value = chars.by_ref().take_while(|c| *c != ',').collect::<String>().trim().to_owned();
+        }
+        params.insert(key, value);
+    }
+    Some(params)
+}
+
+///Helper Function: Path and query of the URL as sent in the request line.
+fn request_target(url: &Url) -> String {
+    match url.query() {
+        Some(q) => format!("{}?{}", url.path(), q),
+        None => url.path().to_owned(),
+    }
+}
+
+fn new_cnonce() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    let count = CNONCE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    md5_hex(&format!("{}:{}", nanos, count))[..16].to_owned()
+}
+
+fn md5_hex(data: &str) -> String {
+    to_hex(&Md5::digest(data.as_bytes()))
+}
+
+fn sha256_hex(data: &str) -> String {
+    to_hex(&Sha256::digest(data.as_bytes()))
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use crate::{http_method::HttpMethod, query_params::QueryParams, ContentType, ParamsLocation};
+
+///BatchRequest: A request of HttpClient::batch. Same parameters as HttpClient::request_with_query.
+#[derive(Debug)]
+pub struct BatchRequest {
+    pub method: HttpMethod,
+    pub url: String,
+    pub extra_headers: Option<HashMap<String, String>>,
+    pub body_params: Option<HashMap<String, String>>,
+    pub query_params: QueryParams,
+    pub content_type: ContentType,
+    pub body_location: ParamsLocation,
+}
+
+impl BatchRequest {
+    ///Request without headers, parameters or body. Content type JSON.
+    pub fn new(method: impl Into<HttpMethod>, url: &str) -> Self {
+        Self {
+            method: method.into(),
+            url: url.to_owned(),
+            extra_headers: None,
+            body_params: None,
+            query_params: QueryParams::new(),
+            content_type: ContentType::JSON,
+            body_location: ParamsLocation::AUTO,
+        }
+    }
+
+    pub fn get(url: &str) -> Self {
+        Self::new(HttpMethod::GET, url)
+    }
+
+    ///Adds a header (chainable).
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.extra_headers.get_or_insert_with(HashMap::new).insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    ///Sets the query parameters (chainable).
+    pub fn query(mut self, query_params: QueryParams) -> Self {
+        self.query_params = query_params;
+        self
+    }
+
+    ///Sets the body parameters and content type (chainable).
+    pub fn body(mut self, body_params: HashMap<String, String>, content_type: ContentType) -> Self {
+        self.body_params = Some(body_params);
+        self.content_type = content_type;
+        self
+    }
+}
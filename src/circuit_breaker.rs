@@ -0,0 +1,173 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use reqwest::Url;
+
+use crate::host::host_key;
+
+///CircuitState: State of the circuit of a host.
+/// CLOSED: Requests are sent. OPEN: Requests fail at once with CircuitOpenError until the cool-down ends.
+/// HALF_OPEN: After the cool-down a few trial requests are sent. A success closes the circuit, a failure opens it again.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CircuitState {
+    CLOSED,
+    OPEN,
+    HALF_OPEN,
+}
+
+///CircuitBreakerConfig: Options of a CircuitBreaker.
+/// failure_threshold: Consecutive failures that open the circuit (default 5).
+/// cool_down: How long the circuit stays open before trial requests are allowed (default 30 seconds).
+/// half_open_requests: Trial requests allowed at the same time when half-open (default 1).
+/// failure_status_codes: Response status codes counted as failures (default 500, 502, 503 and 504). Connection errors always are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub cool_down: Duration,
+    pub half_open_requests: u32,
+    pub failure_status_codes: Vec<u16>,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cool_down: Duration::from_secs(30),
+            half_open_requests: 1,
+            failure_status_codes: vec![500, 502, 503, 504],
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    pub fn new(failure_threshold: u32, cool_down: Duration) -> Self {
+        Self { failure_threshold, cool_down, ..Default::default() }
+    }
+}
+
+///CircuitStatus: Current status of the circuit of a host (e.g. for health endpoints).
+/// consecutive_failures: Failures since the last success.
+/// retry_after: Time left until trial requests are allowed (only when OPEN).
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircuitStatus {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub retry_after: Option<Duration>,
+}
+
+///CircuitOpenError: Returned instead of sending the request when the circuit of the host is open.
+/// Detect it with error.downcast_ref::<CircuitOpenError>().
+#[derive(Clone, Debug, PartialEq)]
+pub struct CircuitOpenError {
+    pub host: String,
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for CircuitOpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Circuit open for {}. Retry after {:?}", self.host, self.retry_after)
+    }
+}
+
+impl std::error::Error for CircuitOpenError {}
+
+#[derive(Debug)]
+struct Circuit {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trials: u32,
+    trials_started: Option<Instant>,
+}
+
+///CircuitBreaker: Stops sending requests to a host (host:port for non default ports) after consecutive failures.
+/// Shared by the clients and sessions it is set on (HttpClientConfig::circuit_breaker or HttpClient::set_circuit_breaker).
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self { config, circuits: Mutex::new(HashMap::new()) }
+    }
+
+    ///Status of the host. CLOSED without failures if no request was sent to it.
+    pub fn get_status(&self, host: &str) -> CircuitStatus {
+        match self.lock().get(&host.to_lowercase()) {
+            Some(c) => self.status(c),
+            None => CircuitStatus { state: CircuitState::CLOSED, consecutive_failures: 0, retry_after: None },
+        }
+    }
+
+    ///Status of every host that received requests.
+    pub fn get_all_status(&self) -> HashMap<String, CircuitStatus> {
+        self.lock().iter().map(|(host, c)| (host.clone(), self.status(c))).collect()
+    }
+
+    ///Closes the circuit of the host and clears its failures.
+    pub fn reset(&self, host: &str) {
+        self.lock().remove(&host.to_lowercase());
+    }
+
+    ///Checks if a request to the URL can be sent. Returns the key of the host, or CircuitOpenError.
+    pub(crate) fn before_request(&self, url: &Url) -> Result<String, CircuitOpenError> {
+        let host = host_key(url);
+        let mut circuits = self.lock();
+        let circuit = circuits.entry(host.clone()).or_insert(Circuit { consecutive_failures: 0, opened_at: None, trials: 0, trials_started: None });
+        if let Some(opened_at) = circuit.opened_at {
+            let elapsed = opened_at.elapsed();
+            if elapsed < self.config.cool_down {
+                return Err(CircuitOpenError { host, retry_after: self.config.cool_down - elapsed });
+            }
+            //Half-open: only some trial requests at the same time. Trials without result after a cool-down (e.g. cancelled) are forgotten
+            if circuit.trials_started.is_some_and(|t| t.elapsed() >= self.config.cool_down) {
+                circuit.trials = 0;
+            }
+            if circuit.trials >= self.config.half_open_requests.max(1) {
+                return Err(CircuitOpenError { host, retry_after: Duration::ZERO });
+            }
+            if circuit.trials == 0 {
+                circuit.trials_started = Some(Instant::now());
+            }
+            circuit.trials += 1;
+        }
+        Ok(host)
+    }
+
+    ///Records the result of a request: status code of the response, or None for a connection error.
+    pub(crate) fn after_request(&self, host: &str, status_code: Option<u16>) {
+        let failed = status_code.is_none_or(|s| self.config.failure_status_codes.contains(&s));
+        let mut circuits = self.lock();
+        let Some(circuit) = circuits.get_mut(host) else { return };
+        if !failed {
+            *circuit = Circuit { consecutive_failures: 0, opened_at: None, trials: 0, trials_started: None };
+            return;
+        }
+        circuit.consecutive_failures += 1;
+        //A failed trial, or too many failures, (re)opens the circuit
+        if circuit.opened_at.is_some() || circuit.consecutive_failures >= self.config.failure_threshold.max(1) {
+            circuit.opened_at = Some(Instant::now());
+            circuit.trials = 0;
+            circuit.trials_started = None;
+        }
+    }
+
+    fn status(&self, circuit: &Circuit) -> CircuitStatus {
+        let (state, retry_after) = match circuit.opened_at {
+            None => (CircuitState::CLOSED, None),
+            Some(opened_at) if opened_at.elapsed() < self.config.cool_down => (CircuitState::OPEN, Some(self.config.cool_down - opened_at.elapsed())),
+            Some(_) => (CircuitState::HALF_OPEN, None),
+        };
+        CircuitStatus { state, consecutive_failures: circuit.consecutive_failures, retry_after }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Circuit>> {
+        self.circuits.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
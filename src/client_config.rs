@@ -0,0 +1,90 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+
+use crate::{auth::HttpAuth, circuit_breaker::CircuitBreaker, concurrency::ConcurrencyLimiter, cookie_jar::CookieJar, dns::DnsConfig, http_cache::HttpCache, middleware::Middleware, proxy::ProxyConfig, rate_limit::RateLimiter, redirect_policy::RedirectPolicy, sigv4::SigV4Config, signing::RequestSigner};
+
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (compatible; BachueTech/1.0)";
+
+///HttpClientConfig: Construction options for HttpClient::with_config.
+/// use_hickory_dns: Enables Hickory DNS resolution.
+/// use_cookies: Enables cookie support.
+/// cookie_jar: Cookie jar to use (e.g. loaded from a file or shared with another client). Enables cookies even if use_cookies is false.
+/// danger_accept_invalid: Same values as HttpClient::new (DANGER_ACCEPT_INVALID_HOSTNAMES or DANGER_ACCEPT_INVALID_CERTS).
+/// user_agent: User-Agent header sent with every request. None uses DEFAULT_USER_AGENT.
+/// default_headers: Initial default headers (same as calling set_header for each one). A User-Agent here overrides user_agent.
+/// header_profiles: Named header sets (e.g. one per upstream). Select one per request with HttpClient::get_header_profile.
+/// auth: Authentication applied to every request (Basic, Bearer or Digest). See HttpClient::set_auth.
+/// sigv4: AWS Signature Version 4 signing of every request. See HttpClient::set_sigv4.
+/// request_signer: Request signing hook (e.g. signing::HmacSigner). Takes precedence over sigv4. See HttpClient::set_request_signer.
+/// redirect_policy: How redirects are followed. The default follows up to 10 redirects. See HttpClient::set_redirect_policy.
+/// proxies: Proxies (HTTP, HTTPS or SOCKS5), checked in order. The first one that applies to the URL is used.
+///          When set, the proxy environment variables are ignored.
+/// ignore_env_proxy: Ignores the proxy environment variables (HTTP_PROXY, HTTPS_PROXY, ALL_PROXY and NO_PROXY, also lowercase).
+///                   By default they are used when proxies is empty.
+/// dns: Static host overrides and resolver options (DnsConfig). Nameservers, TTL limits and cache size need use_hickory_dns.
+/// unix_socket: Path of a Unix domain socket (e.g. /var/run/docker.sock). Every request of the client is sent through it
+///              whatever the URL host (only used for the Host header, e.g. http://localhost/v1.43/containers/json).
+///              Proxies and DNS are not used. Unix only: with_config returns an error on other platforms.
+/// connection: Connection pool, TCP and HTTP/2 options and verbose connection logging (ConnectionConfig).
+/// rate_limiter: Client side rate limiting per host (RateLimiter). Can be shared by several clients. See HttpClient::set_rate_limiter.
+/// concurrency_limiter: Maximum requests in flight, in total and per host (ConcurrencyLimiter). See HttpClient::set_concurrency_limiter.
+/// circuit_breaker: Stops sending requests to a failing host for a while (CircuitBreaker). See HttpClient::set_circuit_breaker.
+/// middleware: Request and response hooks, called in order (see middleware::Middleware). See HttpClient::add_middleware.
+/// cache: HTTP cache of the GET responses (HttpCache). Can be shared by several clients. See HttpClient::set_cache.
+#[derive(Clone, Debug, Default)]
+pub struct HttpClientConfig {
+    pub use_hickory_dns: bool,
+    pub use_cookies: bool,
+    pub cookie_jar: Option<Arc<CookieJar>>,
+    pub danger_accept_invalid: Option<Vec<(String, bool)>>,
+    pub user_agent: Option<String>,
+    pub default_headers: HashMap<String, String>,
+    pub header_profiles: HashMap<String, HashMap<String, String>>,
+    pub auth: Option<HttpAuth>,
+    pub sigv4: Option<SigV4Config>,
+    pub request_signer: Option<Arc<dyn RequestSigner>>,
+    pub redirect_policy: RedirectPolicy,
+    pub proxies: Vec<ProxyConfig>,
+    pub ignore_env_proxy: bool,
+    pub dns: DnsConfig,
+    pub unix_socket: Option<PathBuf>,
+    pub connection: ConnectionConfig,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    pub concurrency_limiter: Option<Arc<ConcurrencyLimiter>>,
+    pub circuit_breaker: Option<Arc<CircuitBreaker>>,
+    pub middleware: Vec<Arc<dyn Middleware>>,
+    pub cache: Option<Arc<HttpCache>>,
+}
+
+impl HttpClientConfig {
+    pub fn new(use_hickory_dns: bool, use_cookies: bool) -> Self {
+        Self {
+            use_hickory_dns,
+            use_cookies,
+            ..Default::default()
+        }
+    }
+}
+
+///ConnectionConfig: Connection pool, TCP and HTTP/2 options. None and false keep the reqwest defaults.
+/// pool_idle_timeout: How long an idle connection is kept in the pool (default 90 seconds).
+/// pool_max_idle_per_host: Maximum idle connections kept per host (default no limit). 0 disables connection reuse.
+/// tcp_keepalive: Interval of TCP keepalive probes (default off).
+/// disable_tcp_nodelay: Turns TCP_NODELAY off (Nagle's algorithm). TCP_NODELAY is on by default.
+/// http1_only: Only uses HTTP/1.1.
+/// http2_prior_knowledge: Only uses HTTP/2, without negotiation (h2c for http:// URLs).
+/// http2_adaptive_window: Adjusts the HTTP/2 flow control window to the connection bandwidth.
+/// http2_keep_alive_interval: Interval of HTTP/2 PING frames that keep idle connections open.
+/// verbose: Logs every read and write of the connections (trace level of the log crate, target reqwest::connect::verbose).
+///          Off by default. Can be changed later with HttpClient::set_connection_verbose.
+#[derive(Clone, Debug, Default)]
+pub struct ConnectionConfig {
+    pub pool_idle_timeout: Option<Duration>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub tcp_keepalive: Option<Duration>,
+    pub disable_tcp_nodelay: bool,
+    pub http1_only: bool,
+    pub http2_prior_knowledge: bool,
+    pub http2_adaptive_window: bool,
+    pub http2_keep_alive_interval: Option<Duration>,
+    pub verbose: bool,
+}
@@ -0,0 +1,77 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use reqwest::Url;
+
+use crate::host::host_key;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+///ConcurrencyLimiter: Maximum number of requests in flight, in total and per host (host:port). Requests over the limit wait.
+/// A request is in flight until its response body has been read (or the HttpStreamResponse is dropped).
+/// Shared by the clients and sessions it is set on (HttpClientConfig::concurrency_limiter or HttpClient::set_concurrency_limiter).
+/// max_total / max_per_host: None is no limit. 0 is treated as 1 (a limit of 0 would block every request forever).
+#[derive(Debug)]
+pub struct ConcurrencyLimiter {
+    max_total: Option<usize>,
+    max_per_host: Option<usize>,
+    total: Arc<Semaphore>,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+///ConcurrencyPermit: Place of a request in the ConcurrencyLimiter. Released when dropped.
+#[derive(Debug)]
+pub struct ConcurrencyPermit {
+    _host: Option<OwnedSemaphorePermit>,
+    _total: Option<OwnedSemaphorePermit>,
+}
+
+impl ConcurrencyLimiter {
+    pub fn new(max_total: Option<usize>, max_per_host: Option<usize>) -> Self {
+        let (max_total, max_per_host) = (max_total.map(|m| m.max(1)), max_per_host.map(|m| m.max(1)));
+        Self {
+            max_total,
+            max_per_host,
+            total: Arc::new(Semaphore::new(max_total.unwrap_or(Semaphore::MAX_PERMITS))),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    ///Waits for a place for a request to the URL.
+    pub async fn acquire(&self, url: &Url) -> ConcurrencyPermit {
+        //Host first: a request waiting for its host does not hold a place of the total
+        let host = match self.max_per_host {
+            Some(max) => {
+                let semaphore = self.lock().entry(host_key(url)).or_insert_with(|| Arc::new(Semaphore::new(max))).clone();
+                semaphore.acquire_owned().await.ok()
+            },
+            None => None,
+        };
+        let total = match self.max_total {
+            Some(_) => self.total.clone().acquire_owned().await.ok(),
+            None => None,
+        };
+        ConcurrencyPermit { _host: host, _total: total }
+    }
+
+    ///Number of requests in flight.
+    pub fn in_flight(&self) -> usize {
+        match self.max_total {
+            Some(max) => max - self.total.available_permits(),
+            None => self.lock().values().map(|s| self.max_per_host.unwrap_or_default() - s.available_permits()).sum(),
+        }
+    }
+
+    ///Number of requests in flight to the host ("host" or "host:port" for non default ports).
+    pub fn in_flight_host(&self, host: &str) -> usize {
+        match (self.max_per_host, self.lock().get(&host.to_lowercase())) {
+            (Some(max), Some(s)) => max - s.available_permits(),
+            _ => 0,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Arc<Semaphore>>> {
+        self.hosts.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,116 @@
+use std::fmt;
+
+use bt_logger::get_error;
+use reqwest::header::{self, HeaderMap, HeaderValue};
+
+use crate::HttpResponse;
+
+///Validators: ETag and Last-Modified of a previous response, used by the conditional requests of HttpClient
+/// (get_if_modified, put_if_match and request_if_match).
+/// Built from a previous HttpResponse, an ETag (&str or String) or with Validators::new.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn new(etag: Option<&str>, last_modified: Option<&str>) -> Self {
+        Self { etag: etag.map(str::to_owned), last_modified: last_modified.map(str::to_owned) }
+    }
+
+    ///Only a Last-Modified date (e.g. "Sun, 06 Nov 1994 08:49:37 GMT").
+    pub fn last_modified(last_modified: &str) -> Self {
+        Self::new(None, Some(last_modified))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    ///Adds If-None-Match and If-Modified-Since (conditional GET).
+    pub(crate) fn set_if_modified(&self, headers: &mut HeaderMap) -> Result<(), Box<dyn std::error::Error>> {
+        set_header(headers, header::IF_NONE_MATCH, self.etag.as_deref())?;
+        set_header(headers, header::IF_MODIFIED_SINCE, self.last_modified.as_deref())
+    }
+
+    ///Adds If-Match, or If-Unmodified-Since when there is no ETag (optimistic concurrency).
+    pub(crate) fn set_if_match(&self, headers: &mut HeaderMap) -> Result<(), Box<dyn std::error::Error>> {
+        match &self.etag {
+            Some(etag) => set_header(headers, header::IF_MATCH, Some(etag)),
+            None => set_header(headers, header::IF_UNMODIFIED_SINCE, self.last_modified.as_deref()),
+        }
+    }
+}
+
+impl From<&HttpResponse> for Validators {
+    fn from(response: &HttpResponse) -> Self {
+        Self { etag: response.header.get("ETag"), last_modified: response.header.get("Last-Modified") }
+    }
+}
+
+impl From<&str> for Validators {
+    fn from(etag: &str) -> Self {
+        Self::new(Some(etag), None)
+    }
+}
+
+impl From<String> for Validators {
+    fn from(etag: String) -> Self {
+        Self { etag: Some(etag), last_modified: None }
+    }
+}
+
+///ConditionalResponse: Result of HttpClient::get_if_modified.
+/// NOT_MODIFIED: The server answered 304. The previous content is still valid (the response has no body).
+/// MODIFIED: New content (or any other answer, e.g. 404).
+#[allow(non_camel_case_types)]
+#[derive(Clone, Debug)]
+pub enum ConditionalResponse {
+    NOT_MODIFIED(HttpResponse),
+    MODIFIED(HttpResponse),
+}
+
+impl ConditionalResponse {
+    pub fn is_modified(&self) -> bool {
+        matches!(self, Self::MODIFIED(_))
+    }
+
+    pub fn response(&self) -> &HttpResponse {
+        match self {
+            Self::NOT_MODIFIED(r) | Self::MODIFIED(r) => r,
+        }
+    }
+
+    ///Validators of the answer, for the next conditional request.
+    pub fn validators(&self) -> Validators {
+        Validators::from(self.response())
+    }
+}
+
+///PreconditionFailedError: Returned by put_if_match and request_if_match when the server answers 412 Precondition Failed:
+/// the resource changed since the validators were read. Detect it with error.downcast_ref::<PreconditionFailedError>().
+/// response: The 412 answer.
+#[derive(Clone, Debug)]
+pub struct PreconditionFailedError {
+    pub url: String,
+    pub response: HttpResponse,
+}
+
+impl fmt::Display for PreconditionFailedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Precondition failed for {}: the resource was modified", self.url)
+    }
+}
+
+impl std::error::Error for PreconditionFailedError {}
+
+fn set_header(headers: &mut HeaderMap, name: header::HeaderName, value: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(value) = value {
+        match HeaderValue::from_str(value) {
+            Ok(v) => { headers.insert(name, v); },
+            Err(e) => return Err(get_error!("set_header", "Invalid {} value '{}'. Error: {}", name, value, e).into()),
+        }
+    }
+    Ok(())
+}
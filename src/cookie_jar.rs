@@ -0,0 +1,324 @@
+use std::{
+    fmt,
+    fs,
+    sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use bt_logger::{get_error, log_warning};
+use cookie::time::OffsetDateTime;
+use cookie_store::{CookieDomain, CookieExpiration, CookieStore, RawCookie};
+use reqwest::{header::HeaderValue, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::request_context::RequestContext;
+
+const NETSCAPE_HEADER: &str = "# Netscape HTTP Cookie File";
+const HTTP_ONLY_PREFIX: &str = "#HttpOnly_";
+
+///CookieFormat: File formats supported by CookieJar save / load.
+/// Json: Array of CookieInfo objects.
+/// Netscape: cookies.txt format used by curl, wget and browser extensions (tab separated, "#HttpOnly_" prefix for HttpOnly cookies).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CookieFormat {
+    Json,
+    Netscape,
+}
+
+///CookieInfo: A stored cookie.
+/// domain: Domain without leading dot. host_only is true when the cookie is only sent to that exact host (no Domain attribute).
+/// expires: Expiration as Unix seconds. None for session cookies.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CookieInfo {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    pub secure: bool,
+    pub http_only: bool,
+    pub host_only: bool,
+    pub expires: Option<u64>,
+}
+
+impl CookieInfo {
+    ///Session cookie for the given host (host only, path "/").
+    pub fn new(name: &str, value: &str, domain: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            value: value.to_owned(),
+            domain: domain.trim_start_matches('.').to_lowercase(),
+            path: "/".to_owned(),
+            secure: false,
+            http_only: false,
+            host_only: true,
+            expires: None,
+        }
+    }
+}
+
+///CookieJar: Cookie store used by HttpClient when cookies are enabled (see HttpClient::get_cookie_jar).
+/// Allows listing, adding and removing cookies and saving / loading them (JSON or Netscape cookies.txt) to keep sessions across restarts.
+/// Expired cookies are never returned nor saved. Session cookies are saved too.
+/// Debug prints the domain and name of the cookies, never their values.
+#[derive(Default)]
+pub struct CookieJar {
+    store: RwLock<CookieStore>,
+}
+
+impl fmt::Debug for CookieJar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cookies: Vec<String> = self.get_cookies(None).iter().map(|c| format!("{}: {}", c.domain, c.name)).collect();
+        f.debug_struct("CookieJar").field("cookies", &cookies).finish()
+    }
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Returns the unexpired cookies. If domain is given, only the cookies set for that domain (not its subdomains).
+    pub fn get_cookies(&self, domain: Option<&str>) -> Vec<CookieInfo> {
+        let domain = domain.map(normalize_domain);
+        self.read()
+            .iter_unexpired()
+            .map(to_cookie_info)
+            .filter(|c| domain.as_ref().is_none_or(|d| *d == c.domain))
+            .collect()
+    }
+
+    ///Returns the value of the named cookie that would be sent to url, if any.
+    pub fn get_cookie_value(&self, url: &str, name: &str) -> Option<String> {
+        let url = Url::parse(url).ok()?;
+        self.read().get_request_values(&url).find(|(n, _)| *n == name).map(|(_, v)| v.to_owned())
+    }
+
+    ///Adds a cookie as if received from url in a Set-Cookie header (e.g. "session=abc; Path=/; HttpOnly").
+    pub fn add_cookie(&self, url: &str, set_cookie: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let url = match Url::parse(url) {
+            Ok(u) => u,
+            Err(e) => return Err(get_error!("add_cookie", "Invalid URL {}. Error: {}", url, e).into()),
+        };
+        let raw = match RawCookie::parse(set_cookie.to_owned()) {
+            Ok(c) => c,
+            Err(e) => return Err(get_error!("add_cookie", "Invalid cookie '{}'. Error: {}", set_cookie, e).into()),
+        };
+        match self.write().insert_raw(&raw, &url) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(get_error!("add_cookie", "Cookie {} rejected for {}. Error: {}", raw.name(), url, e).into()),
+        }
+    }
+
+    ///Adds (or replaces) a cookie. An already expired cookie removes the stored one.
+    pub fn set_cookie(&self, cookie: &CookieInfo) -> Result<(), Box<dyn std::error::Error>> {
+        let domain = normalize_domain(&cookie.domain);
+        let url = match Url::parse(&format!("https://{}{}", domain, cookie.path)) {
+            Ok(u) => u,
+            Err(e) => return Err(get_error!("set_cookie", "Invalid cookie domain {}. Error: {}", cookie.domain, e).into()),
+        };
+        let mut builder = RawCookie::build((cookie.name.clone(), cookie.value.clone()))
+            .path(cookie.path.clone())
+            .secure(cookie.secure)
+            .http_only(cookie.http_only);
+        if !cookie.host_only {
+            builder = builder.domain(domain);
+        }
+        if let Some(exp) = cookie.expires {
+            builder = builder.expires(OffsetDateTime::from_unix_timestamp(exp as i64)?);
+        }
+        match self.write().insert_raw(&builder.build(), &url) {
+            Ok(_) | Err(cookie_store::CookieError::Expired) => Ok(()),
+            Err(e) => Err(get_error!("set_cookie", "Cookie {} rejected for {}. Error: {}", cookie.name, cookie.domain, e).into()),
+        }
+    }
+
+    ///Removes a cookie. Returns true if it was found.
+    pub fn remove_cookie(&self, domain: &str, path: &str, name: &str) -> bool {
+        self.write().remove(&normalize_domain(domain), path, name).is_some()
+    }
+
+    ///Removes every cookie set for the domain. Returns the number of cookies removed.
+    pub fn remove_domain(&self, domain: &str) -> usize {
+        let domain = normalize_domain(domain);
+        let mut store = self.write();
+        let keys: Vec<(String, String)> = store
+            .iter_any()
+            .filter(|c| String::from(&c.domain) == domain)
+            .map(|c| (String::from(&*c.path), c.name().to_owned()))
+            .collect();
+        keys.iter().filter(|(path, name)| store.remove(&domain, path, name).is_some()).count()
+    }
+
+    ///Removes every cookie.
+    pub fn clear(&self) {
+        self.write().clear();
+    }
+
+    ///Returns the unexpired cookies serialized in the given format.
+    pub fn export(&self, format: CookieFormat) -> Result<String, Box<dyn std::error::Error>> {
+        let cookies = self.get_cookies(None);
+        match format {
+            CookieFormat::Json => Ok(serde_json::to_string_pretty(&cookies)?),
+            CookieFormat::Netscape => {
+                let mut out = format!("{}\n", NETSCAPE_HEADER);
+                for c in cookies {
+                    out.push_str(&format!(
+                        "{}{}{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                        if c.http_only { HTTP_ONLY_PREFIX } else { "" },
+                        if c.host_only { "" } else { "." },
+                        c.domain,
+                        netscape_bool(!c.host_only),
+                        c.path,
+                        netscape_bool(c.secure),
+                        c.expires.unwrap_or(0),
+                        c.name,
+                        c.value
+                    ));
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    ///Adds the cookies serialized in the given format (existing cookies with the same domain, path and name are replaced).
+    /// Returns the number of cookies read. Invalid Netscape lines are skipped with a warning.
+    pub fn import(&self, data: &str, format: CookieFormat) -> Result<usize, Box<dyn std::error::Error>> {
+        let cookies: Vec<CookieInfo> = match format {
+            CookieFormat::Json => match serde_json::from_str(data) {
+                Ok(c) => c,
+                Err(e) => return Err(get_error!("import", "Invalid JSON cookie data. Error: {}", e).into()),
+            },
+            CookieFormat::Netscape => data.lines().filter_map(parse_netscape_line).collect(),
+        };
+        for c in &cookies {
+            self.set_cookie(c)?;
+        }
+        Ok(cookies.len())
+    }
+
+    ///Saves the unexpired cookies to a file.
+    pub fn save(&self, path: &str, format: CookieFormat) -> Result<(), Box<dyn std::error::Error>> {
+        let data = self.export(format)?;
+        match fs::write(path, data) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(get_error!("save", "Failed to save cookies to {}. Error: {}", path, e).into()),
+        }
+    }
+
+    ///Loads cookies from a file saved with save (or exported by a browser / curl in Netscape format). Returns the number of cookies read.
+    pub fn load(&self, path: &str, format: CookieFormat) -> Result<usize, Box<dyn std::error::Error>> {
+        match fs::read_to_string(path) {
+            Ok(data) => self.import(&data, format),
+            Err(e) => Err(get_error!("load", "Failed to load cookies from {}. Error: {}", path, e).into()),
+        }
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, CookieStore> {
+        self.store.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, CookieStore> {
+        self.store.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers
+            .filter_map(|v| v.to_str().ok())
+            .filter_map(|v| RawCookie::parse(v.to_owned()).ok());
+        self.write().store_response_cookies(cookies, url);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let value = self.read()
+            .get_request_values(url)
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<String>>()
+            .join("; ");
+        if value.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&value).ok()
+    }
+}
+
+///CookieRouter: Cookie provider of the underlying reqwest Client. Sends each request to the jar of the HttpClient (session)
+/// that executes it, so sessions sharing one connection pool keep separate cookies.
+pub(crate) struct CookieRouter;
+
+impl CookieRouter {
+    ///Jar of the request being executed. None disables cookies for that request.
+    fn current() -> Option<Arc<CookieJar>> {
+        RequestContext::current().and_then(|c| c.cookie_jar)
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieRouter {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        if let Some(jar) = Self::current() {
+            jar.set_cookies(cookie_headers, url);
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        Self::current().and_then(|jar| jar.cookies(url))
+    }
+}
+
+fn to_cookie_info(c: &cookie_store::Cookie<'static>) -> CookieInfo {
+    CookieInfo {
+        name: c.name().to_owned(),
+        value: c.value().to_owned(),
+        domain: String::from(&c.domain),
+        path: String::from(&*c.path),
+        secure: c.secure().unwrap_or(false),
+        http_only: c.http_only().unwrap_or(false),
+        host_only: !matches!(c.domain, CookieDomain::Suffix(_)),
+        expires: match c.expires {
+            CookieExpiration::AtUtc(t) => Some(t.unix_timestamp().max(0) as u64),
+            CookieExpiration::SessionEnd => None,
+        },
+    }
+}
+
+///Helper Function: Parses a cookies.txt line: domain, include subdomains, path, secure, expires, name, value.
+fn parse_netscape_line(line: &str) -> Option<CookieInfo> {
+    let (line, http_only) = match line.strip_prefix(HTTP_ONLY_PREFIX) {
+        Some(l) => (l, true),
+        None => (line, false),
+    };
+    if line.trim().is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let fields: Vec<&str> = line.trim_end_matches(['\r', '\n']).split('\t').collect();
+    if fields.len() != 7 {
+        log_warning!("parse_netscape_line", "Invalid cookies.txt line skipped ({} fields)", fields.len());
+        return None;
+    }
+    let expires = match fields[4].parse::<u64>() {
+        Ok(0) => None,
+        Ok(e) => Some(e),
+        Err(_) => {
+            log_warning!("parse_netscape_line", "Invalid cookie expiration '{}'. Line skipped", fields[4]);
+            return None;
+        }
+    };
+    Some(CookieInfo {
+        name: fields[5].to_owned(),
+        value: fields[6].to_owned(),
+        domain: normalize_domain(fields[0]),
+        path: fields[2].to_owned(),
+        secure: fields[3].eq_ignore_ascii_case("TRUE"),
+        http_only,
+        host_only: !fields[1].eq_ignore_ascii_case("TRUE"),
+        expires,
+    })
+}
+
+fn netscape_bool(value: bool) -> &'static str {
+    if value { "TRUE" } else { "FALSE" }
+}
+
+fn normalize_domain(domain: &str) -> String {
+    domain.trim().trim_start_matches('.').to_lowercase()
+}
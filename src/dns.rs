@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bt_logger::get_error;
+use hickory_resolver::{
+    config::{LookupIpStrategy, NameServerConfig, NameServerConfigGroup, ResolverConfig},
+    name_server::TokioConnectionProvider,
+    proto::xfer::Protocol,
+    TokioResolver,
+};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use tokio::sync::OnceCell;
+
+use crate::request_context::RequestContext;
+
+///IpPreference: Address families used to connect.
+/// BOTH: IPv4 and IPv6 addresses ("happy eyeballs" tries both). IPV4_ONLY / IPV6_ONLY: Only one family.
+/// IPV4_FIRST / IPV6_FIRST: Both families, the preferred one first.
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum IpPreference {
+    #[default]
+    BOTH,
+    IPV4_ONLY,
+    IPV6_ONLY,
+    IPV4_FIRST,
+    IPV6_FIRST,
+}
+
+///DnsConfig: DNS options of HttpClient (HttpClientConfig::dns).
+/// overrides: Static host to IP addresses (like curl --resolve). The port of the URL is kept. Used with both resolvers.
+/// ip_preference: Address families used (IpPreference). Used with both resolvers.
+/// nameservers: Name servers (UDP and TCP) used instead of the system configuration. Hickory DNS only.
+/// min_ttl / max_ttl: Limits to the time DNS answers are cached. Hickory DNS only.
+/// cache_size: Number of DNS records cached. None uses the Hickory default. Hickory DNS only.
+#[derive(Clone, Debug, Default)]
+pub struct DnsConfig {
+    pub overrides: HashMap<String, Vec<IpAddr>>,
+    pub ip_preference: IpPreference,
+    pub nameservers: Vec<SocketAddr>,
+    pub min_ttl: Option<Duration>,
+    pub max_ttl: Option<Duration>,
+    pub cache_size: Option<usize>,
+}
+
+impl DnsConfig {
+    ///Resolves host to the given addresses (chainable).
+    pub fn resolve(mut self, host: &str, addresses: &[IpAddr]) -> Self {
+        self.overrides.insert(host.to_lowercase(), addresses.to_vec());
+        self
+    }
+
+    ///True when an option that needs Hickory DNS is set.
+    fn needs_hickory(&self) -> bool {
+        !self.nameservers.is_empty() || self.min_ttl.is_some() || self.max_ttl.is_some() || self.cache_size.is_some()
+    }
+}
+
+///TimedResolver: DNS resolver of the underlying reqwest Client. Resolves with the overrides, the system resolver or Hickory DNS
+/// and records the lookup time of the current request.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TimedResolver {
+    use_hickory_dns: bool,
+    config: Arc<DnsConfig>,
+    //Created on first use: the client may be built outside a Tokio runtime.
+    hickory: Arc<OnceCell<TokioResolver>>,
+}
+
+impl TimedResolver {
+    ///Returns an error if an option needs Hickory DNS and use_hickory_dns is false.
+    pub(crate) fn new(use_hickory_dns: bool, config: DnsConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        if !use_hickory_dns && config.needs_hickory() {
+            return Err(get_error!("new", "DNS nameservers, TTL limits and cache size require use_hickory_dns").into());
+        }
+        let overrides = config.overrides.into_iter().map(|(host, ips)| (host.to_lowercase(), ips)).collect();
+        Ok(Self { use_hickory_dns, config: Arc::new(DnsConfig { overrides, ..config }), hickory: Arc::new(OnceCell::new()) })
+    }
+
+    async fn lookup(&self, host: &str) -> Result<Vec<SocketAddr>, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(ips) = self.config.overrides.get(&host.to_lowercase()) {
+            return Ok(ips.iter().map(|ip| SocketAddr::new(*ip, 0)).collect());
+        }
+        let addrs: Vec<SocketAddr> = if self.use_hickory_dns {
+            let resolver = self.hickory.get_or_try_init(|| async { new_hickory_resolver(&self.config) }).await?;
+            resolver.lookup_ip(host).await?.into_iter().map(|ip| SocketAddr::new(ip, 0)).collect()
+        } else {
+            tokio::net::lookup_host((host, 0)).await?.collect()
+        };
+        Ok(apply_preference(addrs, self.config.ip_preference))
+    }
+}
+
+impl Resolve for TimedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let resolver = self.clone();
+        Box::pin(async move {
+            let start = Instant::now();
+            let addrs = resolver.lookup(name.as_str()).await;
+            let elapsed = start.elapsed();
+            RequestContext::record(|t| t.dns = Some(elapsed));
+            Ok(Box::new(addrs?.into_iter()) as Addrs)
+        })
+    }
+}
+
+///Helper Function: Hickory resolver. Uses the system configuration (/etc/resolv.conf) unless nameservers are given.
+/// Looks up IPv4 and IPv6 addresses for "happy eyeballs" by default, same as the reqwest hickory-dns resolver.
+fn new_hickory_resolver(config: &DnsConfig) -> Result<TokioResolver, Box<dyn std::error::Error + Send + Sync>> {
+    let mut builder = if config.nameservers.is_empty() {
+        TokioResolver::builder_tokio()?
+    } else {
+        let mut servers = Vec::new();
+        for addr in &config.nameservers {
+            servers.push(NameServerConfig::new(*addr, Protocol::Udp));
+            servers.push(NameServerConfig::new(*addr, Protocol::Tcp));
+        }
+        let resolver_config = ResolverConfig::from_parts(None, vec![], NameServerConfigGroup::from(servers));
+        TokioResolver::builder_with_config(resolver_config, TokioConnectionProvider::default())
+    };
+    let options = builder.options_mut();
+    options.ip_strategy = match config.ip_preference {
+        IpPreference::BOTH => LookupIpStrategy::Ipv4AndIpv6,
+        IpPreference::IPV4_ONLY => LookupIpStrategy::Ipv4Only,
+        IpPreference::IPV6_ONLY => LookupIpStrategy::Ipv6Only,
+        IpPreference::IPV4_FIRST => LookupIpStrategy::Ipv4thenIpv6,
+        IpPreference::IPV6_FIRST => LookupIpStrategy::Ipv6thenIpv4,
+    };
+    if config.min_ttl.is_some() {
+        options.positive_min_ttl = config.min_ttl;
+    }
+    if config.max_ttl.is_some() {
+        options.positive_max_ttl = config.max_ttl;
+    }
+    if let Some(size) = config.cache_size {
+        options.cache_size = size;
+    }
+    Ok(builder.build())
+}
+
+///Helper Function: Filters or orders the addresses by family.
+fn apply_preference(mut addrs: Vec<SocketAddr>, preference: IpPreference) -> Vec<SocketAddr> {
+    match preference {
+        IpPreference::BOTH => (),
+        IpPreference::IPV4_ONLY => addrs.retain(|a| a.is_ipv4()),
+        IpPreference::IPV6_ONLY => addrs.retain(|a| a.is_ipv6()),
+        IpPreference::IPV4_FIRST => addrs.sort_by_key(|a| a.is_ipv6()),
+        IpPreference::IPV6_FIRST => addrs.sort_by_key(|a| a.is_ipv4()),
+    }
+    addrs
+}
@@ -0,0 +1,10 @@
+use reqwest::Url;
+
+///Helper Function: Per-host key of the rate limiter, the concurrency limiter and the circuit breaker:
+/// host, plus ":port" when the URL has a non default port.
+pub(crate) fn host_key(url: &Url) -> String {
+    match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_owned(),
+    }
+}
@@ -0,0 +1,566 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Mutex, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bt_logger::{get_error, log_verbose, log_warning};
+use reqwest::{
+    header::{self, HeaderMap, HeaderName, HeaderValue},
+    Method, Request, Response,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    auth::{redact_url, to_hex},
+    http_headers::HttpHeaders,
+    response_info::{CacheStatus, ResponseInfo},
+    HttpResponse,
+};
+
+const DEFAULT_MAX_SIZE: usize = 10 * 1024 * 1024;
+//Status codes that can be stored (RFC 9110 heuristically cacheable, without the error ones: their body is replaced by HttpClient)
+const CACHEABLE_STATUS: [u16; 6] = [200, 203, 204, 300, 301, 308];
+const CONDITIONAL_HEADERS: [&str; 6] = ["if-none-match", "if-modified-since", "if-match", "if-unmodified-since", "if-range", "range"];
+//Request headers that do not identify the caller. Any other header (e.g. X-Api-Key) makes the request credentialed
+const SHARED_HEADERS: [&str; 20] = [
+    "accept", "accept-charset", "accept-encoding", "accept-language", "cache-control", "connection", "content-length",
+    "content-type", "dnt", "host", "origin", "pragma", "referer", "te", "traceparent", "tracestate", "upgrade-insecure-requests",
+    "user-agent", "x-correlation-id", "x-request-id",
+];
+
+///HttpCacheConfig: Options of an HttpCache.
+/// max_size: Maximum size in bytes of the stored responses (body and headers). The least recently used ones are removed first.
+///           Default 10 MB.
+/// disk_dir: Directory where the responses are also stored, one JSON file per URL, to keep them across restarts.
+///           None (default): memory only.
+/// shared_headers: Request headers, besides the standard ones (Accept, User-Agent, Content-Type...), that do not identify
+///                 the caller. Requests with any other header are treated as requests with credentials (see HttpCache).
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpCacheConfig {
+    pub max_size: usize,
+    pub disk_dir: Option<PathBuf>,
+    pub shared_headers: Vec<String>,
+}
+
+impl Default for HttpCacheConfig {
+    fn default() -> Self {
+        Self { max_size: DEFAULT_MAX_SIZE, disk_dir: None, shared_headers: Vec::new() }
+    }
+}
+
+impl HttpCacheConfig {
+    pub fn new(max_size: usize) -> Self {
+        Self { max_size, ..Default::default() }
+    }
+
+    ///Sets the directory of the on-disk store (chainable).
+    pub fn disk(mut self, disk_dir: impl Into<PathBuf>) -> Self {
+        self.disk_dir = Some(disk_dir.into());
+        self
+    }
+
+    ///Adds a request header that does not identify the caller, e.g. "X-Client-Version" (chainable).
+    pub fn shared_header(mut self, name: &str) -> Self {
+        self.shared_headers.push(name.to_lowercase());
+        self
+    }
+}
+
+///CacheEntry: A stored response and the request header values it was selected with (Vary).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    key: String,
+    vary: Vec<(String, Option<String>)>,
+    status_code: u16,
+    headers: Vec<(String, Vec<u8>)>,
+    body: String,
+    remote_address: String,
+    final_url: String,
+    version: String,
+    //Unix milliseconds
+    response_time: u64,
+    //CacheStore::clock of the last use
+    #[serde(skip)]
+    last_used: u64,
+}
+
+///CacheLookup: Result of HttpCache::lookup, given back with the response to HttpCache::update.
+pub(crate) enum CacheLookup {
+    Bypass,
+    Hit(Box<HttpResponse>),
+    //Unsafe method: the stored responses of the URL are removed when it succeeds
+    Invalidate(String),
+    //credentialed: The request carries credentials (Authorization, cookies or a signature)
+    Miss { key: String, request_headers: HeaderMap, credentialed: bool, stale: Option<Box<CacheEntry>> },
+}
+
+#[derive(Debug, Default)]
+struct CacheStore {
+    entries: HashMap<String, Vec<CacheEntry>>,
+    size: usize,
+    //Incremented on every use, for the least recently used order
+    clock: u64,
+}
+
+impl CacheStore {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+///HttpCache: Shared HTTP cache (RFC 9111) of the GET responses of HttpClient (HttpClientConfig::cache or HttpClient::set_cache).
+/// - Freshness from Cache-Control s-maxage or max-age, or Expires, and the Age and Date headers. No heuristic freshness: responses without
+///   them are only stored if they have a validator (ETag or Last-Modified), and revalidated before each use.
+/// - Stale responses are revalidated with If-None-Match / If-Modified-Since. A 304 answer serves the stored body.
+/// - Honors Cache-Control no-store and no-cache (request and response), max-age (request) and Vary (Vary: * is never stored).
+///   Responses with Cache-Control private are never stored.
+/// - Requests with credentials only store and use responses marked Cache-Control public or s-maxage (RFC 9111 3.5), so a session
+///   never gets the response of another one. Credentials are the authentication, signer or cookies of the client, and any
+///   request header that is not a standard one (Accept, User-Agent...) or in HttpCacheConfig::shared_headers: Authorization,
+///   Cookie, but also API keys (X-Api-Key...) set with set_header, host headers, profiles or extra_headers.
+/// - Requests with their own conditional or Range headers are not answered from the cache. A successful POST, PUT, PATCH or
+///   DELETE removes the stored responses of its URL.
+///
+/// Responses served from the cache are marked in ResponseInfo::cache_status. Can be shared by several clients.
+#[derive(Debug)]
+pub struct HttpCache {
+    config: HttpCacheConfig,
+    store: Mutex<CacheStore>,
+}
+
+impl HttpCache {
+    ///Creates the cache. With disk_dir, creates the directory if needed and loads the responses stored in it.
+    pub fn new(config: HttpCacheConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let cache = Self { config, store: Mutex::new(CacheStore::default()) };
+        if let Some(dir) = &cache.config.disk_dir {
+            if let Err(e) = fs::create_dir_all(dir) {
+                return Err(get_error!("new", "Failed to create cache directory {}. Error: {}", dir.display(), e).into());
+            }
+            let files = match fs::read_dir(dir) {
+                Ok(f) => f,
+                Err(e) => return Err(get_error!("new", "Failed to read cache directory {}. Error: {}", dir.display(), e).into()),
+            };
+            let mut loaded = Vec::new();
+            for path in files.filter_map(|f| f.ok()).map(|f| f.path()).filter(|p| p.extension().is_some_and(|e| e == "json")) {
+                match fs::read_to_string(&path).map(|data| serde_json::from_str::<Vec<CacheEntry>>(&data)) {
+                    Ok(Ok(entries)) => loaded.extend(entries),
+                    _ => log_warning!("new", "Ignoring invalid cache file {}", path.display()),
+                }
+            }
+            //Oldest first: the most recent responses are the last ones removed
+            loaded.sort_by_key(|e| e.response_time);
+            let mut store = cache.lock();
+            for entry in loaded {
+                cache.insert(&mut store, entry);
+            }
+        }
+        Ok(cache)
+    }
+
+    ///Number of stored responses.
+    pub fn len(&self) -> usize {
+        self.lock().entries.values().map(|e| e.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///Size in bytes of the stored responses.
+    pub fn size(&self) -> usize {
+        self.lock().size
+    }
+
+    ///Removes the stored responses of the URL.
+    pub fn remove(&self, url: &str) {
+        let key = reqwest::Url::parse(url).map(|u| cache_key(&u)).unwrap_or(url.to_owned());
+        let mut store = self.lock();
+        self.remove_key(&mut store, &key);
+    }
+
+    ///Removes every stored response (also from disk).
+    pub fn clear(&self) {
+        let mut store = self.lock();
+        let keys: Vec<String> = store.entries.keys().cloned().collect();
+        for key in keys {
+            self.remove_key(&mut store, &key);
+        }
+    }
+
+    ///Checks the request against the cache. Adds the validators to the request when a stale response must be revalidated.
+    /// client_credentials: The client adds credentials to the request (authentication, signer or cookies).
+    pub(crate) fn lookup(&self, request: &mut Request, client_credentials: bool) -> CacheLookup {
+        match *request.method() {
+            Method::GET => (),
+            Method::HEAD | Method::OPTIONS | Method::TRACE => return CacheLookup::Bypass,
+            _ => return CacheLookup::Invalidate(cache_key(request.url())),
+        }
+        let request_cc = cache_control(request.headers());
+        if request_cc.contains_key("no-store") || CONDITIONAL_HEADERS.iter().any(|h| request.headers().contains_key(*h)) {
+            return CacheLookup::Bypass;
+        }
+        let key = cache_key(request.url());
+        let credentialed = client_credentials || self.has_credential_headers(request.headers());
+        let now = now_millis();
+        let stale = {
+            let mut store = self.lock();
+            let tick = store.tick();
+            let Some(entry) = store.entries.get_mut(&key)
+                .and_then(|v| v.iter_mut().find(|e| e.matches(request.headers()) && (!credentialed || e.is_shared()))) else {
+                return CacheLookup::Miss { key, request_headers: request.headers().clone(), credentialed, stale: None };
+            };
+            entry.last_used = tick;
+            let headers = entry.header_map();
+            let age = current_age(&headers, entry.response_time, now);
+            let mut fresh = age < freshness_lifetime(&headers, entry.response_time)
+                && !request_cc.contains_key("no-cache")
+                && !cache_control(&headers).contains_key("no-cache");
+            if let Some(max_age) = request_cc.get("max-age").and_then(|v| parse_seconds(v.as_deref())) {
+                fresh = fresh && age <= max_age;
+            }
+            if fresh {
+                log_verbose!("lookup", "GET {} served from cache (age {} ms)", redact_url(request.url()), age);
+                return CacheLookup::Hit(Box::new(entry.to_response(ResponseInfo::from_cache(&entry.final_url, &entry.version, Some(entry.body.len() as u64), CacheStatus::HIT))));
+            }
+            entry.clone()
+        };
+        //Stale: revalidate when there is a validator, otherwise it is replaced by the new response
+        let headers = stale.header_map();
+        let etag = headers.get(header::ETAG).cloned();
+        let last_modified = headers.get(header::LAST_MODIFIED).cloned();
+        if etag.is_none() && last_modified.is_none() {
+            return CacheLookup::Miss { key, request_headers: request.headers().clone(), credentialed, stale: None };
+        }
+        let request_headers = request.headers().clone();
+        if let Some(etag) = etag {
+            request.headers_mut().insert(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = last_modified {
+            request.headers_mut().insert(header::IF_MODIFIED_SINCE, last_modified);
+        }
+        log_verbose!("lookup", "Revalidating cached GET {}", redact_url(request.url()));
+        CacheLookup::Miss { key, request_headers, credentialed, stale: Some(Box::new(stale)) }
+    }
+
+    ///Stores the response of a lookup MISS if cacheable. Returns the stored response when the server answered 304 to a revalidation.
+    pub(crate) fn update(&self, lookup: CacheLookup, mut response: HttpResponse) -> HttpResponse {
+        match lookup {
+            CacheLookup::Bypass | CacheLookup::Hit(_) => response,
+            CacheLookup::Invalidate(key) => {
+                if response.status_code < 400 {
+                    self.remove_key(&mut self.lock(), &key);
+                }
+                response
+            },
+            CacheLookup::Miss { key, request_headers, credentialed, stale } => {
+                if let (304, Some(stale)) = (response.status_code, stale.as_ref()) {
+                    return self.revalidated(stale, response.header.header_map(), response.info);
+                }
+                response.info.cache_status = CacheStatus::MISS;
+                let mut store = self.lock();
+                match new_entry(&key, &request_headers, &response) {
+                    Some(entry) if !credentialed || entry.is_shared() => self.insert(&mut store, entry),
+                    //Not stored for this session: the responses of the other ones are kept
+                    Some(_) => log_verbose!("update", "Response not stored: request with credentials without Cache-Control public or s-maxage"),
+                    None => self.remove_variant(&mut store, &key, &request_headers),
+                }
+                response
+            },
+        }
+    }
+
+    ///Same as update for streamed responses: they are not stored. Returns the stored response when the server answered 304.
+    pub(crate) fn update_stream(&self, lookup: CacheLookup, response: &Response) -> Option<HttpResponse> {
+        match lookup {
+            CacheLookup::Invalidate(key) if response.status().as_u16() < 400 => {
+                self.remove_key(&mut self.lock(), &key);
+                None
+            },
+            CacheLookup::Miss { stale: Some(stale), .. } if response.status().as_u16() == 304 => {
+                Some(self.revalidated(&stale, response.headers(), ResponseInfo::from_response(response)))
+            },
+            _ => None,
+        }
+    }
+
+    ///Helper Method: True when the request has a header that may identify the caller (not standard nor in shared_headers).
+    fn has_credential_headers(&self, headers: &HeaderMap) -> bool {
+        headers.keys().any(|name| !SHARED_HEADERS.contains(&name.as_str()) && !self.config.shared_headers.iter().any(|s| s.eq_ignore_ascii_case(name.as_str())))
+    }
+
+    ///Helper Method: Updates the stored response with the headers of the 304 answer and returns it.
+    fn revalidated(&self, stale: &CacheEntry, headers_304: &HeaderMap, mut info: ResponseInfo) -> HttpResponse {
+        let mut entry = stale.clone();
+        let mut headers = entry.header_map();
+        for name in headers_304.keys() {
+            if name != header::CONTENT_LENGTH {
+                headers.remove(name);
+            }
+        }
+        for (name, value) in headers_304.iter().filter(|(n, _)| *n != header::CONTENT_LENGTH) {
+            headers.append(name.clone(), value.clone());
+        }
+        entry.headers = to_pairs(&headers);
+        entry.response_time = now_millis();
+        info.content_length = Some(entry.body.len() as u64);
+        info.cache_status = CacheStatus::REVALIDATED;
+        let response = entry.to_response(info);
+        self.insert(&mut self.lock(), entry);
+        response
+    }
+
+    ///Helper Method: Adds the entry (replacing the one with the same Vary values) and removes the least recently used ones over max_size.
+    fn insert(&self, store: &mut CacheStore, mut entry: CacheEntry) {
+        entry.last_used = store.tick();
+        let size = entry.size();
+        let key = entry.key.clone();
+        let variants = store.entries.entry(key.clone()).or_default();
+        let mut removed = 0;
+        variants.retain(|e| {
+            let same = e.vary == entry.vary;
+            if same {
+                removed += e.size();
+            }
+            !same
+        });
+        variants.push(entry);
+        store.size = store.size + size - removed;
+        while store.size > self.config.max_size {
+            let Some((lru_key, index)) = store.entries.iter()
+                .flat_map(|(k, v)| v.iter().enumerate().map(move |(i, e)| (k, i, e.last_used)))
+                .min_by_key(|(_, _, last_used)| *last_used)
+                .map(|(k, i, _)| (k.clone(), i)) else { break };
+            if let Some(variants) = store.entries.get_mut(&lru_key) {
+                store.size -= variants.remove(index).size();
+                if variants.is_empty() {
+                    store.entries.remove(&lru_key);
+                }
+            }
+            if lru_key != key {
+                self.save(store, &lru_key);
+            }
+        }
+        self.save(store, &key);
+    }
+
+    fn remove_key(&self, store: &mut CacheStore, key: &str) {
+        if let Some(variants) = store.entries.remove(key) {
+            store.size -= variants.iter().map(|e| e.size()).sum::<usize>();
+            self.save(store, key);
+        }
+    }
+
+    fn remove_variant(&self, store: &mut CacheStore, key: &str, request_headers: &HeaderMap) {
+        if let Some(variants) = store.entries.get_mut(key) {
+            let before: usize = variants.iter().map(|e| e.size()).sum();
+            variants.retain(|e| !e.matches(request_headers));
+            let after: usize = variants.iter().map(|e| e.size()).sum();
+            if variants.is_empty() {
+                store.entries.remove(key);
+            }
+            store.size -= before - after;
+            if before != after {
+                self.save(store, key);
+            }
+        }
+    }
+
+    ///Helper Method: Writes the responses of the key to disk (removes the file when there are none). Errors are only logged.
+    fn save(&self, store: &CacheStore, key: &str) {
+        let Some(dir) = &self.config.disk_dir else { return };
+        let path = disk_path(dir, key);
+        let result = match store.entries.get(key) {
+            Some(variants) => serde_json::to_string(variants).map_err(|e| e.to_string())
+                .and_then(|data| fs::write(&path, data).map_err(|e| e.to_string())),
+            None if path.exists() => fs::remove_file(&path).map_err(|e| e.to_string()),
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            log_warning!("save", "Failed to update cache file {}. Error: {}", path.display(), e);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheStore> {
+        self.store.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CacheEntry {
+    fn header_map(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            if let (Ok(n), Ok(v)) = (HeaderName::from_str(name), HeaderValue::from_bytes(value)) {
+                headers.append(n, v);
+            }
+        }
+        headers
+    }
+
+    fn matches(&self, request_headers: &HeaderMap) -> bool {
+        self.vary.iter().all(|(name, value)| *value == joined_values(request_headers, name))
+    }
+
+    ///True when the response can be shared by requests with credentials (Cache-Control public or s-maxage).
+    fn is_shared(&self) -> bool {
+        let response_cc = cache_control(&self.header_map());
+        response_cc.contains_key("public") || response_cc.contains_key("s-maxage")
+    }
+
+    fn size(&self) -> usize {
+        self.key.len() + self.body.len() + self.headers.iter().map(|(n, v)| n.len() + v.len()).sum::<usize>()
+    }
+
+    fn to_response(&self, info: ResponseInfo) -> HttpResponse {
+        HttpResponse {
+            status_code: self.status_code,
+            header: HttpHeaders::from(self.header_map()),
+            body: self.body.clone(),
+            remote_address: self.remote_address.clone(),
+            info,
+        }
+    }
+}
+
+///Helper Function: Entry of a response, None when it cannot be stored.
+fn new_entry(key: &str, request_headers: &HeaderMap, response: &HttpResponse) -> Option<CacheEntry> {
+    let headers = response.header.header_map();
+    let response_cc = cache_control(headers);
+    if !CACHEABLE_STATUS.contains(&response.status_code) || response_cc.contains_key("no-store") || response_cc.contains_key("private") {
+        return None;
+    }
+    let now = now_millis();
+    let has_validator = headers.contains_key(header::ETAG) || headers.contains_key(header::LAST_MODIFIED);
+    if freshness_lifetime(headers, now) == 0 && !has_validator {
+        return None;
+    }
+    let mut vary = Vec::new();
+    for name in headers.get_all(header::VARY).iter().filter_map(|v| v.to_str().ok()).flat_map(|v| v.split(',')) {
+        let name = name.trim().to_lowercase();
+        if name == "*" {
+            return None;
+        }
+        if !name.is_empty() {
+            let value = joined_values(request_headers, &name);
+            vary.push((name, value));
+        }
+    }
+    Some(CacheEntry {
+        key: key.to_owned(),
+        vary,
+        status_code: response.status_code,
+        headers: to_pairs(headers),
+        body: response.body.clone(),
+        remote_address: response.remote_address.clone(),
+        final_url: response.info.final_url.clone(),
+        version: response.info.version.clone(),
+        response_time: now,
+        last_used: 0,
+    })
+}
+
+///Helper Function: URL without fragment.
+fn cache_key(url: &reqwest::Url) -> String {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url.to_string()
+}
+
+fn disk_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}.json", to_hex(&Sha256::digest(key.as_bytes()))))
+}
+
+fn to_pairs(headers: &HeaderMap) -> Vec<(String, Vec<u8>)> {
+    headers.iter().map(|(n, v)| (n.as_str().to_owned(), v.as_bytes().to_vec())).collect()
+}
+
+fn joined_values(headers: &HeaderMap, name: &str) -> Option<String> {
+    let values: Vec<String> = headers.get_all(name).iter().map(|v| String::from_utf8_lossy(v.as_bytes()).trim().to_owned()).collect();
+    if values.is_empty() { None } else { Some(values.join(", ")) }
+}
+
+///Helper Function: Cache-Control directives (lowercase names, unquoted values) of every Cache-Control header.
+fn cache_control(headers: &HeaderMap) -> HashMap<String, Option<String>> {
+    headers.get_all(header::CACHE_CONTROL).iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| match d.split_once('=') {
+            Some((name, value)) => (name.trim().to_lowercase(), Some(value.trim().trim_matches('"').to_owned())),
+            None => (d.trim().to_lowercase(), None),
+        })
+        .collect()
+}
+
+///Helper Function: Seconds of a directive or header as milliseconds.
+fn parse_seconds(value: Option<&str>) -> Option<u64> {
+    value?.trim().parse::<u64>().ok().map(|s| s.saturating_mul(1000))
+}
+
+///Helper Function: Freshness lifetime in milliseconds (RFC 9111 4.2.1): s-maxage, max-age, or Expires minus Date. 0 when unknown.
+fn freshness_lifetime(headers: &HeaderMap, response_time: u64) -> u64 {
+    let response_cc = cache_control(headers);
+    if let Some(max_age) = response_cc.get("s-maxage").or(response_cc.get("max-age")).and_then(|v| parse_seconds(v.as_deref())) {
+        return max_age;
+    }
+    //An invalid Expires (e.g. "0") means already expired
+    match headers.get(header::EXPIRES).and_then(|v| v.to_str().ok()) {
+        Some(expires) => parse_http_date(expires).map(|e| e.saturating_sub(date_value(headers, response_time))).unwrap_or_default(),
+        None => 0,
+    }
+}
+
+///Helper Function: Current age in milliseconds (RFC 9111 4.2.3).
+fn current_age(headers: &HeaderMap, response_time: u64, now: u64) -> u64 {
+    let apparent_age = response_time.saturating_sub(date_value(headers, response_time));
+    let age = parse_seconds(headers.get(header::AGE).and_then(|v| v.to_str().ok())).unwrap_or_default();
+    apparent_age.max(age) + now.saturating_sub(response_time)
+}
+
+fn date_value(headers: &HeaderMap, response_time: u64) -> u64 {
+    headers.get(header::DATE).and_then(|v| v.to_str().ok()).and_then(parse_http_date).unwrap_or(response_time)
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default()
+}
+
+///Helper Function: Parses an HTTP date (IMF-fixdate, RFC 850 or asctime) as Unix milliseconds.
+/// "Sun, 06 Nov 1994 08:49:37 GMT", "Sunday, 06-Nov-94 08:49:37 GMT" or "Sun Nov  6 08:49:37 1994".
+fn parse_http_date(date: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    let tokens: Vec<&str> = date.split([' ', ',', '-', ':']).filter(|t| !t.is_empty()).collect();
+    let month_of = |t: &str| MONTHS.iter().position(|m| t.eq_ignore_ascii_case(m)).map(|m| m as u32 + 1);
+    let (day, month, year, time) = match tokens.as_slice() {
+        [_, month, day, h, m, s, year] => (*day, month_of(month)?, *year, [*h, *m, *s]),
+        [_, day, month, year, h, m, s, ..] => (*day, month_of(month)?, *year, [*h, *m, *s]),
+        _ => return None,
+    };
+    let mut year: i64 = year.parse().ok()?;
+    if year < 100 {
+        year += if year < 70 { 2000 } else { 1900 };
+    }
+    let day: u32 = day.parse().ok()?;
+    let [h, m, s] = [time[0].parse::<u64>().ok()?, time[1].parse::<u64>().ok()?, time[2].parse::<u64>().ok()?];
+    if !(1..=31).contains(&day) || h > 23 || m > 59 || s > 60 {
+        return None;
+    }
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some((days * 86_400 + h * 3600 + m * 60 + s) * 1000)
+}
+
+///Helper Function: Days since 1970-01-01 of a date. Howard Hinnant's days_from_civil algorithm.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = i64::from(if month > 2 { month - 3 } else { month + 9 });
+    let doy = (153 * mp + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
@@ -0,0 +1,137 @@
+use std::{collections::HashMap, str::FromStr};
+
+use bt_logger::get_error;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+///HttpHeaders: Represents the headers of an HTTP request or response.
+/// Unlike a HashMap<String, String>, it keeps every value of a repeated header (Set-Cookie, Link, Vary, WWW-Authenticate, ...)
+/// in the order received and keeps the raw bytes of values that are not valid UTF-8.
+/// Header names are case-insensitive in every lookup method.
+///
+/// Methods:
+/// - get(name) -> Option<String>: First value of the header (non UTF-8 bytes are replaced with U+FFFD).
+/// - get_all(name) -> Vec<String>: All values of the header in the order received.
+/// - get_raw(name) / get_all_raw(name): Same as get and get_all but returning the raw bytes.
+/// - contains(name) -> bool: True if the header is present.
+/// - insert(name, value) / append(name, value): Replace or add a value.
+/// - to_hashmap() -> HashMap: Legacy flattened view. Repeated values are joined with ", ".
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HttpHeaders {
+    headers: HeaderMap,
+}
+
+impl HttpHeaders {
+    pub fn new() -> Self {
+        Self { headers: HeaderMap::new() }
+    }
+
+    ///Returns the first value of the header as a string. Invalid UTF-8 bytes are replaced, never dropped.
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.headers.get(name).map(|v| String::from_utf8_lossy(v.as_bytes()).to_string())
+    }
+
+    ///Returns all the values of the header, in the order received. Empty if the header is not present.
+    pub fn get_all(&self, name: &str) -> Vec<String> {
+        self.headers
+            .get_all(name)
+            .iter()
+            .map(|v| String::from_utf8_lossy(v.as_bytes()).to_string())
+            .collect()
+    }
+
+    ///Returns the raw bytes of the first value of the header.
+    pub fn get_raw(&self, name: &str) -> Option<&[u8]> {
+        self.headers.get(name).map(|v| v.as_bytes())
+    }
+
+    ///Returns the raw bytes of all the values of the header, in the order received.
+    pub fn get_all_raw(&self, name: &str) -> Vec<&[u8]> {
+        self.headers.get_all(name).iter().map(|v| v.as_bytes()).collect()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.headers.contains_key(name)
+    }
+
+    ///Number of header values (a header with two values counts twice).
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    ///Returns the distinct header names (lowercase).
+    pub fn names(&self) -> Vec<String> {
+        self.headers.keys().map(|k| k.to_string()).collect()
+    }
+
+    ///Iterates over every (name, raw value) pair. Repeated headers yield one pair per value.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.headers.iter().map(|(k, v)| (k.as_str(), v.as_bytes()))
+    }
+
+    ///Replaces any existing value of the header with the new value.
+    pub fn insert(&mut self, name: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let (n, v) = parse_header(name, value)?;
+        self.headers.insert(n, v);
+        Ok(())
+    }
+
+    ///Adds a new value to the header keeping the existing ones.
+    pub fn append(&mut self, name: &str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let (n, v) = parse_header(name, value)?;
+        self.headers.append(n, v);
+        Ok(())
+    }
+
+    ///Removes all the values of the header.
+    pub fn remove(&mut self, name: &str) {
+        self.headers.remove(name);
+    }
+
+    pub(crate) fn header_map(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    ///Flattened view for code using HashMap<String, String>. Repeated values are joined with ", ".
+    pub fn to_hashmap(&self) -> HashMap<String, String> {
+        let mut map: HashMap<String, String> = HashMap::new();
+        for (k, v) in self.headers.iter() {
+            let value = String::from_utf8_lossy(v.as_bytes());
+            map.entry(k.to_string())
+                .and_modify(|existing| {
+                    existing.push_str(", ");
+                    existing.push_str(&value);
+                })
+                .or_insert_with(|| value.to_string());
+        }
+        map
+    }
+}
+
+impl From<HeaderMap> for HttpHeaders {
+    fn from(headers: HeaderMap) -> Self {
+        Self { headers }
+    }
+}
+
+impl From<&HeaderMap> for HttpHeaders {
+    fn from(headers: &HeaderMap) -> Self {
+        Self { headers: headers.clone() }
+    }
+}
+
+///Helper Function: Validates a header name and value.
+pub(crate) fn parse_header(name: &str, value: &str) -> Result<(HeaderName, HeaderValue), Box<dyn std::error::Error>> {
+    let n = match HeaderName::from_str(name) {
+        Ok(n) => n,
+        Err(e) => return Err(get_error!("parse_header", "Invalid header name '{}'. Error: {}", name, e).into()),
+    };
+    let v = match HeaderValue::from_str(value) {
+        Ok(v) => v,
+        Err(e) => return Err(get_error!("parse_header", "Invalid value for header '{}'. Error: {}", name, e).into()),
+    };
+    Ok((n, v))
+}
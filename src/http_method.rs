@@ -0,0 +1,86 @@
+use std::fmt;
+
+use bt_logger::get_error;
+use reqwest::Method;
+
+///HttpMethod: HTTP method used by HttpClient::request.
+/// CUSTOM: Any other method (e.g. WebDAV PROPFIND, MKCOL, COPY, MOVE, LOCK). Sent exactly as written (methods are case-sensitive).
+/// Converting from a string is case-insensitive for the standard methods ("get" is GET). Other names become CUSTOM.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HttpMethod {
+    GET,
+    POST,
+    PUT,
+    DELETE,
+    PATCH,
+    HEAD,
+    OPTIONS,
+    TRACE,
+    CONNECT,
+    CUSTOM(String),
+}
+
+impl HttpMethod {
+    pub fn as_str(&self) -> &str {
+        match self {
+            HttpMethod::GET => "GET",
+            HttpMethod::POST => "POST",
+            HttpMethod::PUT => "PUT",
+            HttpMethod::DELETE => "DELETE",
+            HttpMethod::PATCH => "PATCH",
+            HttpMethod::HEAD => "HEAD",
+            HttpMethod::OPTIONS => "OPTIONS",
+            HttpMethod::TRACE => "TRACE",
+            HttpMethod::CONNECT => "CONNECT",
+            HttpMethod::CUSTOM(m) => m,
+        }
+    }
+
+    ///False for GET, HEAD, TRACE and CONNECT: body parameters are not sent with them.
+    pub fn allows_body(&self) -> bool {
+        !matches!(self, HttpMethod::GET | HttpMethod::HEAD | HttpMethod::TRACE | HttpMethod::CONNECT)
+    }
+
+    ///Converts to a reqwest Method. Returns an error if a CUSTOM method is not a valid token (e.g. empty or with spaces).
+    pub(crate) fn to_method(&self) -> Result<Method, Box<dyn std::error::Error>> {
+        match Method::from_bytes(self.as_str().as_bytes()) {
+            Ok(m) => Ok(m),
+            Err(_) => Err(get_error!("to_method", "Invalid HTTP method: '{}'", self.as_str()).into()),
+        }
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for HttpMethod {
+    fn from(method: &str) -> Self {
+        match method.to_uppercase().as_str() {
+            "GET" => HttpMethod::GET,
+            "POST" => HttpMethod::POST,
+            "PUT" => HttpMethod::PUT,
+            "DELETE" => HttpMethod::DELETE,
+            "PATCH" => HttpMethod::PATCH,
+            "HEAD" => HttpMethod::HEAD,
+            "OPTIONS" => HttpMethod::OPTIONS,
+            "TRACE" => HttpMethod::TRACE,
+            "CONNECT" => HttpMethod::CONNECT,
+            _ => HttpMethod::CUSTOM(method.to_owned()),
+        }
+    }
+}
+
+impl From<String> for HttpMethod {
+    fn from(method: String) -> Self {
+        HttpMethod::from(method.as_str())
+    }
+}
+
+impl From<&String> for HttpMethod {
+    fn from(method: &String) -> Self {
+        HttpMethod::from(method.as_str())
+    }
+}
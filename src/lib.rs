@@ -1,41 +1,120 @@
 /// Defines a HttpClient struct and its associated methods, which provides a simple and efficient way to make HTTP requests.
 /// It includes methods to set custom headers and retrieve default headers, as well as handling cookies if needed.
 /// It also defines an HttpResponse struct to represent the response from a HTTP request.
+pub mod auth;
+pub mod batch;
+pub mod circuit_breaker;
+pub mod client_config;
+pub mod concurrency;
+pub mod conditional;
+pub mod cookie_jar;
+pub mod dns;
+#[allow(clippy::needless_late_init, clippy::collapsible_if, clippy::unnecessary_map_or, clippy::needless_return, clippy::absurd_extreme_comparisons, clippy::len_zero)]
 mod ext_certs;
+mod host;
+pub mod http_cache;
+pub mod http_headers;
+pub mod http_method;
+pub mod middleware;
+pub mod oauth2;
+pub mod proxy;
+pub mod query_params;
+pub mod rate_limit;
+pub mod redirect_policy;
+mod request_context;
+pub mod response_info;
+pub mod sigv4;
+pub mod signing;
 pub mod stream_response;
+mod transport;
+pub mod url_template;
 
 pub const DANGER_ACCEPT_INVALID_HOSTNAMES: &str = "danger_accept_invalid_hostnames";
 pub const DANGER_ACCEPT_INVALID_CERTS: &str = "danger_accept_invalid_certs";
 
 use std::{
-    collections::HashMap, str::FromStr, sync::Arc
+    collections::HashMap, str::FromStr, sync::Arc, time::Instant
 };
 
+use auth::{redact_url, set_authorization, HttpAuth};
 use bt_logger::{get_error, log_error, log_verbose, log_warning};
-use ext_certs::get_local_certificates;
+use batch::BatchRequest;
+use circuit_breaker::{CircuitBreaker, CircuitOpenError};
+use client_config::{HttpClientConfig, DEFAULT_USER_AGENT};
+use concurrency::ConcurrencyLimiter;
+use conditional::{ConditionalResponse, PreconditionFailedError, Validators};
+use cookie_jar::CookieJar;
+use futures_util::{stream, StreamExt};
+use http_cache::{CacheLookup, HttpCache};
+use http_headers::{parse_header, HttpHeaders};
+use http_method::HttpMethod;
+use middleware::{Middleware, MiddlewareRequest};
+use rate_limit::RateLimiter;
+use redirect_policy::{same_origin, RedirectHop, RedirectPolicy};
+use request_context::RequestContext;
+use response_info::{ResponseInfo, ResponseTrace};
+use sigv4::{SigV4Config, SigV4Signer};
+use signing::RequestSigner;
 use reqwest::{
-    cookie::Jar, header::{self, HeaderMap, HeaderName, HeaderValue}, Client, Method, Response, StatusCode
+    cookie::CookieStore, header::{self, HeaderMap, HeaderName, HeaderValue}, Client, Method, RequestBuilder, Response, StatusCode, Url
 };
 use stream_response::HttpStreamResponse;
+use transport::Transport;
+use query_params::QueryParams;
+use url_template::{TemplateValue, UrlTemplate};
 
 ///HttpClient:
 ///client: A Client instance from the reqwest crate for making HTTP requests.
 ///headers: A HeaderMap to store custom headers.
+///header_profiles: Named header sets that can be selected per request.
+///host_headers: Default headers only sent to a specific host (key: host or host:port, lowercase).
+///base_url: Set on scoped clients. Relative paths are resolved against it.
+///scope_headers: Set on scoped clients. Only sent to URLs under base_url.
+///auth: Authentication (Basic, Bearer or Digest) applied to every request (only under base_url on scoped clients).
+///signer: Request signing hook (HMAC, AWS SigV4 or custom) applied to every request (only under base_url on scoped clients).
+///cookie_jar: The cookie store when cookies are enabled. Each session has its own.
+///redirect_policy: How redirects are followed (RedirectPolicy).
+///transport: Builds client (the underlying reqwest Client) with or without verbose connection logging.
+///connection_verbose: True when client logs the connections.
+///rate_limiter: Client side rate limiter shared with sessions and copies of the client.
+///concurrency_limiter: Limit of requests in flight shared with sessions and copies of the client.
+///circuit_breaker: Circuit breaker per host shared with sessions and copies of the client.
+///middleware: Request and response hooks, in registration order.
+///cache: HTTP cache of the GET responses shared with sessions and copies of the client.
+#[derive(Clone)]
 pub struct HttpClient {
     client: Client,
     headers: HeaderMap,
+    header_profiles: HashMap<String, HeaderMap>,
+    host_headers: HashMap<String, HeaderMap>,
+    base_url: Option<Url>,
+    scope_headers: HeaderMap,
+    auth: Option<HttpAuth>,
+    signer: Option<Arc<dyn RequestSigner>>,
+    cookie_jar: Option<Arc<CookieJar>>,
+    redirect_policy: RedirectPolicy,
+    transport: Arc<Transport>,
+    connection_verbose: bool,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    concurrency_limiter: Option<Arc<ConcurrencyLimiter>>,
+    circuit_breaker: Option<Arc<CircuitBreaker>>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    cache: Option<Arc<HttpCache>>,
 }
 
 ///HttpResponse: Represents the response from an HTTP request.
 /// status_code: The status code of the HTTP response.
-/// header: A HashMap containing the headers from the response.
+/// header: The headers from the response (HttpHeaders). Keeps repeated headers and raw values.
 /// body: The body content of the HTTP response as a string.
+/// remote_address: IP address of the server ("0.0.0.0" when unknown).
+/// info: Final URL, HTTP version, remote port, redirects, content length and timings (ResponseInfo).
 #[derive(Clone, Debug)]
 pub struct HttpResponse {
     pub status_code: u16,
-    pub header: HashMap<String, String>,
+    pub header: HttpHeaders,
     pub body: String,
-    pub remote_address: String
+    pub remote_address: String,
+    pub info: ResponseInfo,
 }
 
 
@@ -46,6 +125,17 @@ pub enum ContentType {
     TEXT,
 }
 
+///ParamsLocation: Where HttpClient::request_with_location puts body_params.
+/// AUTO: In the body, except for methods without body (GET, HEAD, TRACE, CONNECT) where they are discarded with a warning.
+/// BODY: Always in the body, also for GET (e.g. search APIs that take a JSON body with GET).
+/// QUERY: Always in the query string.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParamsLocation {
+    AUTO,
+    BODY,
+    QUERY,
+}
+
 impl HttpClient {
     ///Constructor new: 
     /// The new method is used to create a new instance of the HttpClient struct
@@ -57,69 +147,331 @@ impl HttpClient {
     ///                         Possible values: const DANGER_ACCEPT_INVALID_HOSTNAMES: &str = "danger_accept_invalid_hostnames" OR
     ///                                          const DANGER_ACCEPT_INVALID_CERTS: &str = "danger_accept_invalid_certs" OR
     pub fn new(use_hickory_dns: bool, use_cookies: bool, danger_accept_invalid: Option<Vec<(String,bool)>>) -> Self {
-        let tls_conn = get_local_certificates(danger_accept_invalid);
-        let mut cb = Client::builder();
-
-        if use_cookies {
-            let cookie_store = Arc::new(Jar::default());
-            if let Some (reqwest_tc) = tls_conn{
-                cb = cb
-                    .use_native_tls()
-                    .use_preconfigured_tls(reqwest_tc);
-            }
-                cb = cb.cookie_provider(cookie_store.clone())
-                /*.hickory_dns(use_hickory_dns)
-                .build()
-                .unwrap()*/
-        } else {
-            if let Some (reqwest_tc) = tls_conn{
-                cb = cb
-                    .use_native_tls()
-                    .use_preconfigured_tls(reqwest_tc);
-            }
-            cb = cb.cookie_store(false)
-                /*.hickory_dns(use_hickory_dns)
-                .build()
-                .unwrap()*/
+        let config = HttpClientConfig {
+            danger_accept_invalid,
+            ..HttpClientConfig::new(use_hickory_dns, use_cookies)
         };
+        Self::with_config(config).unwrap()
+    }
 
-        let c = cb
-        .connection_verbose(true)
-        //.danger_accept_invalid_certs(true)
-        //.danger_accept_invalid_hostnames(true)
-        .hickory_dns(use_hickory_dns)
-        .build()
-        .unwrap();
+    ///Constructor with_config:
+    /// Creates a new HttpClient from an HttpClientConfig. Same behavior as new plus the configurable user agent,
+    /// initial default headers and named header profiles.
+    /// Returns an error if a configured header is invalid or the underlying client cannot be built.
+    pub fn with_config(config: HttpClientConfig) -> Result<Self, Box<dyn std::error::Error>> {
+        let (transport, c) = Transport::new(&config)?;
+        let cookie_jar = if config.use_cookies || config.cookie_jar.is_some() {
+            Some(config.cookie_jar.unwrap_or_default())
+        } else {
+            None
+        };
 
         let mut h = HeaderMap::new();
-        h.insert(
-            header::USER_AGENT,
-            HeaderValue::from_static("Mozilla/5.0 (compatible; BachueTech/1.0)"),
-        );
+        let (ua_name, ua_value) = parse_header(header::USER_AGENT.as_str(), config.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT))?;
+        h.insert(ua_name, ua_value);
+        for (key, value) in config.default_headers {
+            let (n, v) = parse_header(&key, &value)?;
+            h.insert(n, v);
+        }
+
+        let mut profiles: HashMap<String, HeaderMap> = HashMap::new();
+        for (profile, headers) in config.header_profiles {
+            profiles.insert(profile, to_header_map(&headers)?);
+        }
 
-        Self {
+        Ok(Self {
             client: c,
             headers: h,
-        }
+            header_profiles: profiles,
+            host_headers: HashMap::new(),
+            base_url: None,
+            scope_headers: HeaderMap::new(),
+            auth: config.auth,
+            signer: config.request_signer.or(config.sigv4.map(|c| Arc::new(SigV4Signer::new(c)) as Arc<dyn RequestSigner>)),
+            cookie_jar,
+            redirect_policy: config.redirect_policy,
+            transport,
+            connection_verbose: config.connection.verbose,
+            rate_limiter: config.rate_limiter,
+            concurrency_limiter: config.concurrency_limiter,
+            circuit_breaker: config.circuit_breaker,
+            middleware: config.middleware,
+            cache: config.cache,
+        })
     }
 
     ///Method set_header: Allows adding custom headers to the HTTP client dynamically.
     pub fn set_header(&mut self, header_name: &str, header_value: &str) {
         self.headers.insert(
-            HeaderName::from_str(&header_name).unwrap(),
-            HeaderValue::from_str(&header_value).unwrap(),
+            HeaderName::from_str(header_name).unwrap(),
+            HeaderValue::from_str(header_value).unwrap(),
         );
     }
 
+    ///Method append_header: Adds a new value to a default header keeping the existing values (e.g. several Accept or Cookie values).
+    /// Returns an error if the header name or value is invalid.
+    pub fn append_header(&mut self, header_name: &str, header_value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let (n, v) = parse_header(header_name, header_value)?;
+        self.headers.append(n, v);
+        Ok(())
+    }
+
     ///Method get_default_headers: Converts the internal HeaderMap to a HashMap for easy access and manipulation.
+    /// Repeated headers are joined with ", ". Use get_default_http_headers to get every value.
     pub fn get_default_headers(&self) -> HashMap<String, String> {
-        convert_headers(&self.headers)
+        HttpHeaders::from(&self.headers).to_hashmap()
+    }
+
+    ///Method get_default_http_headers: Returns the default headers as HttpHeaders keeping repeated values.
+    pub fn get_default_http_headers(&self) -> HttpHeaders {
+        HttpHeaders::from(&self.headers)
+    }
+
+    ///Method set_header_profile: Adds or replaces a named header profile (e.g. one per upstream).
+    /// Returns an error if a header name or value is invalid.
+    pub fn set_header_profile(&mut self, profile_name: &str, headers: HashMap<String, String>) -> Result<(), Box<dyn std::error::Error>> {
+        self.header_profiles.insert(profile_name.to_owned(), to_header_map(&headers)?);
+        Ok(())
+    }
+
+    ///Method remove_header_profile: Removes a named header profile.
+    pub fn remove_header_profile(&mut self, profile_name: &str) {
+        self.header_profiles.remove(profile_name);
+    }
+
+    ///Method get_header_profile: Returns the headers of a named profile, ready to be used as extra_headers in
+    /// get, post, post_stream or request. The profile headers are merged over the default headers for that call only.
+    /// Returns None (and logs a warning) if the profile does not exist.
+    pub fn get_header_profile(&self, profile_name: &str) -> Option<HashMap<String, String>> {
+        match self.header_profiles.get(profile_name) {
+            Some(h) => Some(HttpHeaders::from(h).to_hashmap()),
+            None => {
+                log_warning!("get_header_profile", "Header profile '{}' not found", profile_name);
+                None
+            }
+        }
+    }
+
+    ///Method set_host_header: Adds a default header only sent to requests for the given host.
+    /// host can be a host name (any port) or host:port. Useful to keep an Authorization header from leaking to other hosts.
+    /// Returns an error if the header name or value is invalid.
+    pub fn set_host_header(&mut self, host: &str, header_name: &str, header_value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let (n, v) = parse_header(header_name, header_value)?;
+        self.host_headers.entry(host.to_lowercase()).or_default().insert(n, v);
+        Ok(())
+    }
+
+    ///Method remove_host_headers: Removes all the default headers of a host.
+    pub fn remove_host_headers(&mut self, host: &str) {
+        self.host_headers.remove(&host.to_lowercase());
+    }
+
+    ///Method scoped: Creates a sub-client bound to base_url. It shares the connection pool (and cookies) with this client.
+    /// Relative paths given to get, post, post_stream and request are resolved against base_url ("users" and "/users" with
+    /// base "https://api.example.com/v1" both become "https://api.example.com/v1/users"). Absolute URLs are used as is.
+    /// scope_headers (and headers added later with set_scope_header) are only sent to URLs under base_url.
+    pub fn scoped(&self, base_url: &str, scope_headers: Option<HashMap<String, String>>) -> Result<HttpClient, Box<dyn std::error::Error>> {
+        let mut base = match Url::parse(base_url) {
+            Ok(u) => u,
+            Err(e) => return Err(get_error!("scoped", "Invalid base URL '{}'. Error: {}", base_url, e).into()),
+        };
+        if !base.path().ends_with('/') {
+            let path = format!("{}/", base.path());
+            base.set_path(&path);
+        }
+
+        let mut scoped_client = self.clone();
+        scoped_client.base_url = Some(base);
+        scoped_client.scope_headers = match scope_headers {
+            Some(sh) => to_header_map(&sh)?,
+            None => HeaderMap::new(),
+        };
+        Ok(scoped_client)
+    }
+
+    ///Method session: Creates a session that shares the connection pool with this client but has its own cookie jar
+    /// (cookie_jar, or a new empty one if None) and its own copy of the default headers, host headers, profiles and authentication.
+    /// Changes made to the session are not seen by this client and vice versa. Cookies work in sessions even if this client has them disabled.
+    pub fn session(&self, cookie_jar: Option<Arc<CookieJar>>) -> HttpClient {
+        let mut session = self.clone();
+        session.cookie_jar = Some(cookie_jar.unwrap_or_default());
+        session
+    }
+
+    ///Method set_connection_verbose: Turns the logging of every read and write of the connections on or off
+    /// (trace level of the log crate, target reqwest::connect::verbose). Applies to the connections opened after the change.
+    /// Returns an error if the underlying client cannot be built.
+    pub fn set_connection_verbose(&mut self, verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
+        self.client = self.transport.client(verbose)?;
+        self.connection_verbose = verbose;
+        Ok(())
+    }
+
+    ///Method is_connection_verbose: True when the connections are logged.
+    pub fn is_connection_verbose(&self) -> bool {
+        self.connection_verbose
+    }
+
+    ///Method set_rate_limiter: Sets (or removes with None) the client side rate limiter. Requests over the limit of their host
+    /// wait for their turn. Sessions and copies created afterwards share it.
+    pub fn set_rate_limiter(&mut self, rate_limiter: Option<Arc<RateLimiter>>) {
+        self.rate_limiter = rate_limiter;
+    }
+
+    ///Method get_rate_limiter: Returns the rate limiter (e.g. to read its statistics).
+    pub fn get_rate_limiter(&self) -> Option<Arc<RateLimiter>> {
+        self.rate_limiter.clone()
+    }
+
+    ///Method set_concurrency_limiter: Sets (or removes with None) the limit of requests in flight, in total and per host.
+    /// Requests over the limit wait. Sessions and copies created afterwards share it.
+    pub fn set_concurrency_limiter(&mut self, concurrency_limiter: Option<Arc<ConcurrencyLimiter>>) {
+        self.concurrency_limiter = concurrency_limiter;
+    }
+
+    ///Method get_concurrency_limiter: Returns the concurrency limiter (e.g. to read the requests in flight).
+    pub fn get_concurrency_limiter(&self) -> Option<Arc<ConcurrencyLimiter>> {
+        self.concurrency_limiter.clone()
+    }
+
+    ///Method set_circuit_breaker: Sets (or removes with None) the circuit breaker. Requests to a host with an open circuit
+    /// fail at once with circuit_breaker::CircuitOpenError (returned as is, not wrapped). Sessions and copies created afterwards share it.
+    pub fn set_circuit_breaker(&mut self, circuit_breaker: Option<Arc<CircuitBreaker>>) {
+        self.circuit_breaker = circuit_breaker;
+    }
+
+    ///Method get_circuit_breaker: Returns the circuit breaker (e.g. to report the state of each host).
+    pub fn get_circuit_breaker(&self) -> Option<Arc<CircuitBreaker>> {
+        self.circuit_breaker.clone()
+    }
+
+    ///Method add_middleware: Adds a middleware after the ones already registered (see middleware::Middleware).
+    /// Sessions and copies created afterwards share it.
+    pub fn add_middleware(&mut self, middleware: Arc<dyn Middleware>) {
+        self.middleware.push(middleware);
     }
 
-    ///Helper Method: Merge current/default headers with extra headers
+    ///Method clear_middleware: Removes every middleware.
+    pub fn clear_middleware(&mut self) {
+        self.middleware.clear();
+    }
+
+    ///Method get_middleware: Returns the middleware in registration order.
+    pub fn get_middleware(&self) -> Vec<Arc<dyn Middleware>> {
+        self.middleware.clone()
+    }
+
+    ///Method set_cache: Sets (or removes with None) the HTTP cache of the GET responses (see http_cache::HttpCache).
+    /// Responses served from it are marked in ResponseInfo::cache_status. Sessions and copies created afterwards share it.
+    pub fn set_cache(&mut self, cache: Option<Arc<HttpCache>>) {
+        self.cache = cache;
+    }
+
+    ///Method get_cache: Returns the HTTP cache (e.g. to clear it).
+    pub fn get_cache(&self) -> Option<Arc<HttpCache>> {
+        self.cache.clone()
+    }
+
+    ///Method set_redirect_policy: Sets how redirects are followed (RedirectPolicy). The default follows up to 10 redirects.
+    pub fn set_redirect_policy(&mut self, redirect_policy: RedirectPolicy) {
+        self.redirect_policy = redirect_policy;
+    }
+
+    ///Method get_redirect_policy: Returns a copy of the redirect policy.
+    pub fn get_redirect_policy(&self) -> RedirectPolicy {
+        self.redirect_policy.clone()
+    }
+
+    ///Method with_redirect_policy: Returns a copy of this client (same connection pool, cookie jar, headers and authentication)
+    /// with another redirect policy. Use it for single requests, e.g. client.with_redirect_policy(RedirectPolicy::none()).get(url, None).
+    pub fn with_redirect_policy(&self, redirect_policy: RedirectPolicy) -> HttpClient {
+        let mut client = self.clone();
+        client.redirect_policy = redirect_policy;
+        client
+    }
+
+    ///Method set_scope_header: Adds a header only sent to URLs under the base URL of a scoped client.
+    /// Returns an error if this client is not scoped or the header is invalid.
+    pub fn set_scope_header(&mut self, header_name: &str, header_value: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if self.base_url.is_none() {
+            return Err(get_error!("set_scope_header", "Client is not scoped. Use scoped(base_url) to create a scoped client").into());
+        }
+        let (n, v) = parse_header(header_name, header_value)?;
+        self.scope_headers.insert(n, v);
+        Ok(())
+    }
+
+    ///Method set_auth: Sets (or removes with None) the authentication used by this client: HttpAuth::Basic, HttpAuth::Bearer or HttpAuth::Digest.
+    /// On a scoped client the credentials are only sent to URLs under its base URL.
+    /// An Authorization header given in extra_headers takes precedence for that request.
+    pub fn set_auth(&mut self, auth: Option<HttpAuth>) {
+        self.auth = auth;
+    }
+
+    ///Method set_request_signer: Sets (or removes with None) the request signing hook, e.g. signing::HmacSigner.
+    /// The signer is called last, after headers and authentication, right before each request (and retry) is sent.
+    /// On a scoped client only URLs under its base URL are signed. Replaces any signer set with set_sigv4.
+    pub fn set_request_signer(&mut self, signer: Option<Arc<dyn RequestSigner>>) {
+        self.signer = signer;
+    }
+
+    ///Method set_sigv4: Enables (or disables with None) AWS Signature Version 4 signing of every request (S3, MinIO and other SigV4 APIs).
+    /// Same as set_request_signer with a SigV4Signer. The signature replaces any Authorization header.
+    pub fn set_sigv4(&mut self, config: Option<SigV4Config>) {
+        self.signer = config.map(|c| Arc::new(SigV4Signer::new(c)) as Arc<dyn RequestSigner>);
+    }
+
+    ///Method get_base_url: Returns the base URL of a scoped client.
+    pub fn get_base_url(&self) -> Option<String> {
+        self.base_url.as_ref().map(|u| u.to_string())
+    }
+
+    ///Method get_cookie_jar: Returns the cookie jar (None if cookies are disabled). Use it to list, add, remove, save or load cookies.
+    /// Changes are seen by the next request. Scoped clients share the jar of their parent.
+    pub fn get_cookie_jar(&self) -> Option<Arc<CookieJar>> {
+        self.cookie_jar.clone()
+    }
+
+    ///Helper Method: Resolves the url against the base URL of a scoped client. Absolute URLs are returned as is.
+    fn resolve_url(&self, url: &str) -> Result<Url, Box<dyn std::error::Error>> {
+        match (Url::parse(url), &self.base_url) {
+            (Ok(u), _) => Ok(u),
+            (Err(url::ParseError::RelativeUrlWithoutBase), Some(base)) => match base.join(url.trim_start_matches('/')) {
+                Ok(u) => Ok(u),
+                Err(e) => Err(get_error!("resolve_url", "Invalid path '{}' for base URL {}. Error: {}", url, base, e).into()),
+            },
+            (Err(e), _) => Err(get_error!("resolve_url", "Invalid URL '{}'. Error: {}", url, e).into()),
+        }
+    }
+
+    ///Helper Method: True if the url is under the base URL of this scoped client (same origin and path prefix).
+    fn in_scope(&self, url: &Url) -> bool {
+        match &self.base_url {
+            Some(base) => base.origin() == url.origin() && format!("{}/", url.path()).starts_with(base.path()),
+            None => false,
+        }
+    }
+
+    ///Helper Method: Merge current/default headers, host headers, scope headers and extra headers (in that order)
     //fn get_extra_headers(&self, extra_headers: Option<HashMap<&str, &str>>) -> HeaderMap {
-    fn get_extra_headers(&self, extra_headers: Option<HashMap<String, String>>) -> HeaderMap {
+    fn get_extra_headers(&self, url: &Url, extra_headers: Option<HashMap<String, String>>) -> HeaderMap {
         let mut local_headers = self.headers.clone();
+        if let Some(host) = url.host_str() {
+            let host = host.to_lowercase();
+            let host_port = format!("{}:{}", host, url.port_or_known_default().unwrap_or_default());
+            for key in [host, host_port] {
+                if let Some(hh) = self.host_headers.get(&key) {
+                    for (n, v) in hh {
+                        local_headers.insert(n.clone(), v.clone());
+                    }
+                }
+            }
+        }
+        if self.in_scope(url) {
+            for (n, v) in &self.scope_headers {
+                local_headers.insert(n.clone(), v.clone());
+            }
+        }
         if let Some(new_headers) = extra_headers {
             // Add headers from HashMap into the existing HeaderMap
             for (key, value) in new_headers {
@@ -139,11 +491,28 @@ impl HttpClient {
 /// The method returns an HttpResponse instance containing the response from the GET request. 
 //    pub async fn get( &self, url: &str, extra_headers: Option<HashMap<&str, &str>>, ) -> Result<HttpResponse, Error> {
     pub async fn get( &self, url: &str, extra_headers: Option<HashMap<String, String>>, ) -> Result<HttpResponse, Box<dyn std::error::Error>> {
-        let local_headers = self.get_extra_headers(extra_headers);
-        match self.client.get(url).headers(local_headers).send().await {
-            Ok(resp) => return Ok(Self::extract_response(resp, url, "GET").await),
+        let target = self.resolve_url(url)?;
+        let local_headers = self.get_extra_headers(&target, extra_headers);
+        match self.send_http(self.client.get(target.clone()).headers(local_headers)).await {
+            Ok(resp) => Ok(resp),
+            Err(e) if e.is::<CircuitOpenError>() => Err(e),
             Err(e) => {
-                return Err(get_error!( "get", "Failed to get response from GET: {}. Error: {}", url, e).into())
+                Err(get_error!( "get", "Failed to get response from GET: {}. Error: {}", redact_url(&target), e).into())
+            }
+        }
+    }
+
+///Method: head
+/// The head method makes a HEAD request (e.g. to check that a resource exists or get its size from Content-Length without downloading it).
+/// The body of the returned HttpResponse is always empty.
+    pub async fn head( &self, url: &str, extra_headers: Option<HashMap<String, String>>, ) -> Result<HttpResponse, Box<dyn std::error::Error>> {
+        let target = self.resolve_url(url)?;
+        let local_headers = self.get_extra_headers(&target, extra_headers);
+        match self.send_http(self.client.head(target.clone()).headers(local_headers)).await {
+            Ok(resp) => Ok(resp),
+            Err(e) if e.is::<CircuitOpenError>() => Err(e),
+            Err(e) => {
+                Err(get_error!( "head", "Failed to get response from HEAD: {}. Error: {}", redact_url(&target), e).into())
             }
         }
     }
@@ -156,7 +525,8 @@ impl HttpClient {
     pub async fn post( &self, url: &str, extra_headers: Option<HashMap<String, String>>, body_request: &str, content_type: ContentType, ) 
                         -> Result<HttpResponse,  Box<dyn std::error::Error>> {
         //log_verbose!("post", "Getting {} with payload: {}", url, body_request);
-        let mut local_headers = self.get_extra_headers(extra_headers); //self.headers.clone();
+        let target = self.resolve_url(url)?;
+        let mut local_headers = self.get_extra_headers(&target, extra_headers); //self.headers.clone();
         match content_type {
             ContentType::JSON => {
                 local_headers.insert(
@@ -172,24 +542,25 @@ impl HttpClient {
             }
         }
 
-        match self
+        match self.send_http(self
             .client
-            .post(url)
+            .post(target.clone())
             .headers(local_headers)
-            .body(body_request.to_string())
-            .send()
+            .body(body_request.to_string()))
             .await
         {
-            Ok(resp) => return Ok(Self::extract_response(resp, url, "POST").await),
+            Ok(resp) => Ok(resp),
+            Err(e) if e.is::<CircuitOpenError>() => Err(e),
             Err(e) => {
-                return Err(get_error!( "post", "Failed to get response from POST ({:?}): {}. Error: {}", content_type, url, e ).into() )
+                Err(get_error!( "post", "Failed to get response from POST ({:?}): {}. Error: {}", content_type, redact_url(&target), e ).into() )
             }
         }
     }
 
     pub async fn post_stream( &self, url: &str, extra_headers: Option<HashMap<String, String>>, body_request: &str, content_type: ContentType, ) -> Result<HttpStreamResponse,  Box<dyn std::error::Error>> {
         //log_verbose!("post", "Getting {} with payload: {}", url, body_request);
-        let mut local_headers = self.get_extra_headers(extra_headers); //self.headers.clone();
+        let target = self.resolve_url(url)?;
+        let mut local_headers = self.get_extra_headers(&target, extra_headers); //self.headers.clone();
         match content_type {
             ContentType::JSON => {
                 local_headers.insert(
@@ -205,57 +576,183 @@ impl HttpClient {
             }
         }
 
-        match self
+        match self.send_stream(self
             .client
-            .post(url)
+            .post(target.clone())
             .headers(local_headers)
-            .body(body_request.to_string())
-            .send()
+            .body(body_request.to_string()))
             .await
         {
-            Ok(resp) => return Ok(HttpStreamResponse::new(resp)),
+            Ok(resp) => Ok(resp),
+            Err(e) if e.is::<CircuitOpenError>() => Err(e),
             Err(e) => {
-                return Err(get_error!( "post_stream", "Failed to get stream response from POST ({:?}): {}. Error: {}", content_type, url, e ).into() )
+                Err(get_error!( "post_stream", "Failed to get stream response from POST ({:?}): {}. Error: {}", content_type, redact_url(&target), e ).into() )
             }
         }
     }
 
 ///Method: request
-/// The request method is used to make a request to a specific URL using a specific HTTP method: currently tested, get, post, put, delete, patch, delete
+/// The request method is used to make a request to a specific URL using any HTTP method: an HttpMethod or its name
+/// ("GET", "post", "HEAD", "OPTIONS", "PROPFIND"...). Names other than the standard methods are sent as custom methods.
+/// HEAD responses have an empty body.
 /// It takes six parameters: request_method, url_with_ep_path (URL with endpoint: path, path parameters), extra_headers, body_params, query_params, and content_type. 
+/// query_params fill the placeholders of the URL template (see url_template::UrlTemplate: {name} values are percent-encoded,
+/// a missing value is an error). The remaining ones are added to the query string for every method.
+/// Braces that are not a well-formed placeholder (e.g. {"a":1} or an unclosed brace) are sent percent-encoded.
+/// body_params are sent in the body. Methods without body (GET, HEAD, TRACE, CONNECT) discard them with a warning: use
+/// request_with_location to send them in the query string or in the body.
 /// The method returns an HttpResponse instance containing the response from the request.
 //    pub async fn request( &self, request_method: &str, url_with_ep_path: &str, extra_headers: Option<HashMap<&str, &str>>, body_params: Option<HashMap<String, String>>, 
-    pub async fn request( &self, request_method: &str, url_with_ep_path: &str, extra_headers: Option<HashMap<String, String>>, body_params: Option<HashMap<String, String>>, 
+    pub async fn request( &self, request_method: impl Into<HttpMethod>, url_with_ep_path: &str, extra_headers: Option<HashMap<String, String>>, body_params: Option<HashMap<String, String>>, 
                         query_params: Option<HashMap<String, String>>, content_type: ContentType, ) -> Result<HttpResponse, Box<dyn std::error::Error>> {
-        let method = match request_method.to_uppercase().as_str() {
-            "GET" => Method::GET,
-            "POST" => Method::POST,
-            "PUT" => Method::PUT,
-            "DELETE" => Method::DELETE,
-            "PATCH" => Method::PATCH,
-            _ => return Err(get_error!("request", "Unsupported HTTP method: {}", &request_method).into()),
-        };
+        self.request_with_location(request_method, url_with_ep_path, extra_headers, body_params, query_params, content_type, ParamsLocation::AUTO).await
+    }
 
-        let mut url = url_with_ep_path.to_string();
-        let mut qry_params: HashMap<String, String>;
-
-        // Handle path parameters
-        if let Some(path_params) = query_params {
-            qry_params = path_params.clone();
-            for path_param in path_params {
-                if url.contains(&format!("{{{}}}", &path_param.0)) {
-                    url = url.replace(&format!("{{{}}}", &path_param.0), &path_param.1);
-                    qry_params.remove(&path_param.0); //Remove used path_param to use remaining params as query parameters
-                } else {
-                    log_verbose!("request","Path parameter '{:?}' not provided. Parameter will be used as Query parameter", &path_param.0);
-                }
+///Method: request_with_location
+/// Same as request with an explicit location for body_params (ParamsLocation): AUTO, BODY (e.g. GET with a JSON body) or QUERY.
+/// Parameters are never silently dropped: AUTO logs a warning when it discards them, and BODY with a CONNECT request is an error.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn request_with_location( &self, request_method: impl Into<HttpMethod>, url_with_ep_path: &str, extra_headers: Option<HashMap<String, String>>,
+                        body_params: Option<HashMap<String, String>>, query_params: Option<HashMap<String, String>>, content_type: ContentType,
+                        body_location: ParamsLocation, ) -> Result<HttpResponse, Box<dyn std::error::Error>> {
+        let query_params = query_params.map(QueryParams::from).unwrap_or_default();
+        self.request_with_query(request_method, url_with_ep_path, extra_headers, body_params, query_params, content_type, body_location).await
+    }
+
+///Method: request_with_query
+/// Same as request_with_location with ordered query parameters (QueryParams): repeated names (?tag=a&tag=b), the given order,
+/// or a Serialize struct (QueryParams::from_serialize). Repeated parameters used by the URL template are expanded as lists.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn request_with_query( &self, request_method: impl Into<HttpMethod>, url_with_ep_path: &str, extra_headers: Option<HashMap<String, String>>,
+                        body_params: Option<HashMap<String, String>>, query_params: QueryParams, content_type: ContentType,
+                        body_location: ParamsLocation, ) -> Result<HttpResponse, Box<dyn std::error::Error>> {
+        let http_method: HttpMethod = request_method.into();
+        let (request, target) = self.build_request(&http_method, url_with_ep_path, extra_headers, body_params, query_params, &content_type, body_location)?;
+
+        match self.send_http(request)
+            .await
+        {
+            Ok(resp) => Ok(resp),
+            Err(e) if e.is::<CircuitOpenError>() => Err(e),
+            Err(e) => {
+                Err(get_error!( "request", "Failed to get response from {} ({:?}): {}. Error: {}", &http_method, content_type, redact_url(&target), e)
+                                    .into())
+            }
+        }
+    }
+
+///Method: request_stream
+/// Same as request_with_query but returns the response as a stream (HttpStreamResponse), like post_stream, for any method.
+    pub async fn request_stream( &self, request_method: impl Into<HttpMethod>, url_with_ep_path: &str, extra_headers: Option<HashMap<String, String>>,
+                        body_params: Option<HashMap<String, String>>, query_params: QueryParams, content_type: ContentType, ) 
+                        -> Result<HttpStreamResponse, Box<dyn std::error::Error>> {
+        let http_method: HttpMethod = request_method.into();
+        let (request, target) = self.build_request(&http_method, url_with_ep_path, extra_headers, body_params, query_params, &content_type, ParamsLocation::AUTO)?;
+
+        match self.send_stream(request).await {
+            Ok(resp) => Ok(resp),
+            Err(e) if e.is::<CircuitOpenError>() => Err(e),
+            Err(e) => {
+                Err(get_error!( "request_stream", "Failed to get stream response from {} ({:?}): {}. Error: {}", &http_method, content_type, redact_url(&target), e ).into() )
             }
-        }else{
-            qry_params = HashMap::new();
         }
+    }
+
+///Method: get_if_modified
+/// Conditional GET: sends If-None-Match and If-Modified-Since from validators (a previous HttpResponse, an ETag or Validators).
+/// Returns ConditionalResponse::NOT_MODIFIED when the server answers 304, MODIFIED with the new content otherwise.
+    pub async fn get_if_modified( &self, url: &str, validators: impl Into<Validators>, extra_headers: Option<HashMap<String, String>>, )
+                        -> Result<ConditionalResponse, Box<dyn std::error::Error>> {
+        let target = self.resolve_url(url)?;
+        let mut local_headers = self.get_extra_headers(&target, extra_headers);
+        validators.into().set_if_modified(&mut local_headers)?;
+        match self.send_http(self.client.get(target.clone()).headers(local_headers)).await {
+            Ok(resp) if resp.status_code == StatusCode::NOT_MODIFIED.as_u16() => Ok(ConditionalResponse::NOT_MODIFIED(resp)),
+            Ok(resp) => Ok(ConditionalResponse::MODIFIED(resp)),
+            Err(e) if e.is::<CircuitOpenError>() => Err(e),
+            Err(e) => {
+                Err(get_error!( "get_if_modified", "Failed to get response from GET: {}. Error: {}", redact_url(&target), e).into())
+            }
+        }
+    }
+
+///Method: put_if_match
+/// Optimistic concurrency update: PUT sent with If-Match (or If-Unmodified-Since when validators have no ETag).
+/// Returns a PreconditionFailedError (not wrapped) when the server answers 412 because the resource changed.
+    pub async fn put_if_match( &self, url: &str, validators: impl Into<Validators>, extra_headers: Option<HashMap<String, String>>, body_request: &str,
+                        content_type: ContentType, ) -> Result<HttpResponse, Box<dyn std::error::Error>> {
+        self.request_if_match(HttpMethod::PUT, url, validators, extra_headers, Some(body_request), content_type).await
+    }
+
+///Method: request_if_match
+/// Same as put_if_match with any method (e.g. PATCH or DELETE). body_request None sends no body.
+    pub async fn request_if_match( &self, request_method: impl Into<HttpMethod>, url: &str, validators: impl Into<Validators>,
+                        extra_headers: Option<HashMap<String, String>>, body_request: Option<&str>, content_type: ContentType, )
+                        -> Result<HttpResponse, Box<dyn std::error::Error>> {
+        let http_method: HttpMethod = request_method.into();
+        let target = self.resolve_url(url)?;
+        let mut local_headers = self.get_extra_headers(&target, extra_headers);
+        validators.into().set_if_match(&mut local_headers)?;
+        let mut request = self.client.request(http_method.to_method()?, target.clone());
+        if let Some(body) = body_request {
+            let mime = match content_type {
+                ContentType::JSON => "application/json",
+                ContentType::TEXT => "application/text",
+            };
+            local_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(mime));
+            request = request.body(body.to_string());
+        }
+
+        match self.send_http(request.headers(local_headers)).await {
+            Ok(resp) if resp.status_code == StatusCode::PRECONDITION_FAILED.as_u16() => {
+                log_warning!("request_if_match", "{} {}: precondition failed (412)", &http_method, redact_url(&target));
+                Err(Box::new(PreconditionFailedError { url: redact_url(&target), response: resp }))
+            },
+            Ok(resp) => Ok(resp),
+            Err(e) if e.is::<CircuitOpenError>() => Err(e),
+            Err(e) => {
+                Err(get_error!( "request_if_match", "Failed to get response from {} ({:?}): {}. Error: {}", &http_method, content_type, redact_url(&target), e).into())
+            }
+        }
+    }
+
+    ///Method batch: Sends the requests with at most max_parallel of them in flight (at least 1). Returns the results in the order
+    /// of the requests: an error for a request does not stop the others. The concurrency limiter and rate limiter also apply.
+    pub async fn batch(&self, requests: Vec<BatchRequest>, max_parallel: usize) -> Vec<Result<HttpResponse, Box<dyn std::error::Error>>> {
+        stream::iter(requests)
+            .map(|r| async move {
+                self.request_with_query(r.method, &r.url, r.extra_headers, r.body_params, r.query_params, r.content_type, r.body_location).await
+            })
+            .buffered(max_parallel.max(1))
+            .collect()
+            .await
+    }
 
+ ///Helper Method: build_request
+ /// Expands the URL template, adds the remaining query parameters (in order) and the body parameters. Shared by request and request_stream.
+    #[allow(clippy::too_many_arguments)]
+    fn build_request(&self, http_method: &HttpMethod, url_with_ep_path: &str, extra_headers: Option<HashMap<String, String>>, body_params: Option<HashMap<String, String>>,
+                        mut query_params: QueryParams, content_type: &ContentType, body_location: ParamsLocation) -> Result<(RequestBuilder, Url), Box<dyn std::error::Error>> {
+        let method = http_method.to_method()?;
 
-        let mut local_headers = self.get_extra_headers(extra_headers); //self.headers.clone();
+        // Handle path parameters: expand the URL template. Parameters not used by the template are query parameters
+        let template = UrlTemplate::parse_lenient(url_with_ep_path);
+        let mut template_values: HashMap<String, TemplateValue> = HashMap::new();
+        for name in template.variables() {
+            let mut values = query_params.remove(&name);
+            match values.len() {
+                0 => {}
+                1 => { template_values.insert(name, TemplateValue::Str(values.remove(0))); }
+                _ => { template_values.insert(name, TemplateValue::List(values)); }
+            }
+        }
+        if !query_params.is_empty() {
+            log_verbose!("request","Parameters {:?} not used in the URL. They will be used as Query parameters", query_params.names());
+        }
+        let url = template.expand(&template_values)?;
+
+        let target = self.resolve_url(&url)?;
+        let mut local_headers = self.get_extra_headers(&target, extra_headers); //self.headers.clone();
 
         match content_type {
             ContentType::JSON => {
@@ -277,11 +774,19 @@ impl HttpClient {
         //    url = format!("{}{}",url,"/");
         //}
 
-        let mut request = self.client.request(method.clone(), &url).headers(local_headers);
-        if method == Method::GET{
-            request = request.query(&qry_params); // Use remaining params as query parameters if any
-        }else{
-            if let Some(b_params) = body_params{
+        let mut request = self.client.request(method, target.clone()).headers(local_headers);
+        if !query_params.is_empty() {
+            request = request.query(query_params.pairs()); // Use remaining params as query parameters if any
+        }
+        if let Some(b_params) = body_params {
+            if body_location == ParamsLocation::AUTO && !http_method.allows_body() {
+                log_warning!("request", "{} requests have no body. Body parameters {:?} are discarded (use ParamsLocation::QUERY or BODY)",
+                    http_method, b_params.keys().collect::<Vec<&String>>());
+            } else if body_location == ParamsLocation::QUERY {
+                request = request.query(QueryParams::from(b_params).pairs());
+            } else if *http_method == HttpMethod::CONNECT {
+                return Err(get_error!("request", "CONNECT requests cannot have a body. Body parameters would be discarded").into());
+            } else {
                     match content_type {
                         ContentType::JSON => request = request.json(&b_params),
                         _ => {let body_data = b_params
@@ -294,16 +799,270 @@ impl HttpClient {
                     }       
             }
         }
+        Ok((request, target))
+    }
 
-        match request
-            .send()
-            .await
-        {
-            Ok(resp) => return Ok(Self::extract_response(resp, &url, request_method.to_uppercase().as_str()).await),
-            Err(e) => {
-                return Err(get_error!( "request", "Failed to get response from {} ({:?}): {}. Error: {}", &method, content_type, url, e)
-                                    .into())
+ ///Helper Method: send_http
+ /// Builds the request, runs the middleware around send and reads the response.
+    async fn send_http(&self, request_builder: RequestBuilder) -> Result<HttpResponse, Box<dyn std::error::Error>> {
+        let mut request = request_builder.build().map_err(|e| e.without_url())?;
+        let (ran, short_circuit) = self.run_on_request(&mut request)?;
+        let sent = (ran > 0).then(|| MiddlewareRequest::from_request(&request));
+        let mut response = match short_circuit {
+            Some(r) => r,
+            None => self.send_cached(request).await?,
+        };
+        if let Some(sent) = sent {
+            for m in self.middleware[..ran].iter().rev() {
+                m.on_response(&sent, &mut response)?;
+            }
+        }
+        Ok(response)
+    }
+
+ ///Helper Method: send_stream
+ /// Same as send_http without reading the body (HttpStreamResponse).
+    async fn send_stream(&self, request_builder: RequestBuilder) -> Result<HttpStreamResponse, Box<dyn std::error::Error>> {
+        let mut request = request_builder.build().map_err(|e| e.without_url())?;
+        let (ran, short_circuit) = self.run_on_request(&mut request)?;
+        let sent = (ran > 0).then(|| MiddlewareRequest::from_request(&request));
+        let mut response = match short_circuit {
+            Some(r) => HttpStreamResponse::from_http_response(r),
+            None => self.send_stream_cached(request).await?,
+        };
+        if let Some(sent) = sent {
+            for m in self.middleware[..ran].iter().rev() {
+                m.on_stream_response(&sent, &mut response)?;
+            }
+        }
+        Ok(response)
+    }
+
+ ///Helper Method: send_cached
+ /// Sends the request through the HTTP cache (when set) and reads the response.
+    async fn send_cached(&self, mut request: reqwest::Request) -> Result<HttpResponse, Box<dyn std::error::Error>> {
+        let lookup = match &self.cache {
+            Some(cache) => {
+                let credentialed = self.has_credentials(&request);
+                cache.lookup(&mut request, credentialed)
+            },
+            None => CacheLookup::Bypass,
+        };
+        if let CacheLookup::Hit(response) = lookup {
+            return Ok(*response);
+        }
+        let (url, method) = (redact_url(request.url()), request.method().clone());
+        let response = Self::extract_response(self.send(request).await?, &url, method.as_str()).await;
+        match &self.cache {
+            Some(cache) => Ok(cache.update(lookup, response)),
+            None => Ok(response),
+        }
+    }
+
+ ///Helper Method: send_stream_cached
+ /// Same as send_cached without reading the body. Streamed responses are served from the cache but not stored.
+    async fn send_stream_cached(&self, mut request: reqwest::Request) -> Result<HttpStreamResponse, Box<dyn std::error::Error>> {
+        let lookup = match &self.cache {
+            Some(cache) => {
+                let credentialed = self.has_credentials(&request);
+                cache.lookup(&mut request, credentialed)
+            },
+            None => CacheLookup::Bypass,
+        };
+        if let CacheLookup::Hit(response) = lookup {
+            return Ok(HttpStreamResponse::from_http_response(*response));
+        }
+        let resp = self.send(request).await?;
+        match self.cache.as_ref().and_then(|c| c.update_stream(lookup, &resp)) {
+            Some(response) => Ok(HttpStreamResponse::from_http_response(response)),
+            None => Ok(HttpStreamResponse::new(resp)),
+        }
+    }
+
+ ///Helper Method: has_credentials
+ /// True when the client adds credentials to the request: authentication, signer or cookies of the cookie jar for the URL.
+ /// The cache checks the headers of the request itself (Authorization, API keys...).
+    fn has_credentials(&self, request: &reqwest::Request) -> bool {
+        let applies = self.base_url.is_none() || self.in_scope(request.url());
+        (applies && (self.auth.is_some() || self.signer.is_some()))
+            || self.cookie_jar.as_ref().is_some_and(|jar| jar.cookies(request.url()).is_some())
+    }
+
+ ///Helper Method: run_on_request
+ /// Calls on_request of the middleware in order. Returns how many ran without short-circuiting and the short-circuit response.
+    fn run_on_request(&self, request: &mut reqwest::Request) -> Result<(usize, Option<HttpResponse>), Box<dyn std::error::Error>> {
+        for (i, m) in self.middleware.iter().enumerate() {
+            if let Some(response) = m.on_request(request)? {
+                log_verbose!("run_on_request", "Request to {} answered by middleware {:?}", redact_url(request.url()), m);
+                return Ok((i, Some(response)));
+            }
+        }
+        Ok((self.middleware.len(), None))
+    }
+
+ ///Helper Method: send
+ /// Sends the request applying the configured authentication.
+ /// On a 401 answer the request is sent again once: with Digest authentication when there is a WWW-Authenticate: Digest challenge,
+ /// and with OAuth2 after renewing the token.
+    async fn send(&self, mut request: reqwest::Request) -> Result<Response, Box<dyn std::error::Error>> {
+        let applies = self.base_url.is_none() || self.in_scope(request.url());
+        let auth = match &self.auth {
+            Some(a) if applies && !request.headers().contains_key(header::AUTHORIZATION) => Some(a),
+            _ => None,
+        };
+        let signer = if applies { self.signer.as_ref() } else { None };
+        let mut retry_request = match auth {
+            Some(HttpAuth::Digest { .. }) | Some(HttpAuth::OAuth2(_)) => request.try_clone(),
+            _ => None,
+        };
+        let mut oauth2_token = String::new();
+        match auth {
+            Some(HttpAuth::OAuth2(provider)) => {
+                oauth2_token = provider.get_token(&self.client).await?;
+                set_authorization(&mut request, &format!("Bearer {}", oauth2_token));
+            },
+            Some(a) => a.authorize(&mut request),
+            None => (),
+        }
+
+        if let Some(s) = signer {
+            s.sign(&mut request)?;
+        }
+        let resp = self.execute(request).await?;
+        if resp.status() != StatusCode::UNAUTHORIZED {
+            return Ok(resp);
+        }
+        match (auth, retry_request.as_mut()) {
+            (Some(digest @ HttpAuth::Digest { .. }), Some(retry)) => {
+                let challenge = resp.headers().get_all(header::WWW_AUTHENTICATE).iter()
+                    .map(|v| String::from_utf8_lossy(v.as_bytes()).to_string())
+                    .find(|v| v.trim_start().to_lowercase().starts_with("digest "));
+                match challenge.and_then(|c| digest.digest_response(&c, retry)) {
+                    Some(authorization) => retry.headers_mut().insert(header::AUTHORIZATION, authorization),
+                    None => {
+                        log_warning!("send", "401 from {} without a supported Digest challenge", redact_url(resp.url()));
+                        return Ok(resp);
+                    },
+                };
+            },
+            (Some(HttpAuth::OAuth2(provider)), Some(retry)) => {
+                log_verbose!("send", "401 from {}. Renewing OAuth2 token and retrying once", redact_url(resp.url()));
+                provider.invalidate(&oauth2_token).await;
+                let token = provider.get_token(&self.client).await?;
+                set_authorization(retry, &format!("Bearer {}", token));
+            },
+            _ => return Ok(resp),
+        }
+        match retry_request {
+            Some(mut retry) => {
+                //Releases the connection and the concurrency limiter place of the 401 answer
+                drop(resp);
+                if let Some(s) = signer {
+                    s.sign(&mut retry)?;
+                }
+                self.execute(retry).await
+            },
+            None => Ok(resp),
+        }
+    }
+
+ ///Helper Method: execute
+ /// Sends the request with the underlying client. The URL is removed from the error (it is logged redacted by the callers).
+    async fn execute(&self, request: reqwest::Request) -> Result<Response, Box<dyn std::error::Error>> {
+        let context = RequestContext::new(self.cookie_jar.clone());
+        let trace = context.trace.clone();
+        let start = Instant::now();
+        let permit = match &self.concurrency_limiter {
+            Some(limiter) => Some(Arc::new(limiter.acquire(request.url()).await)),
+            None => None,
+        };
+        //reqwest reads the cookies when execute is called, so the calls must happen inside the scope
+        let (mut resp, redirects) = context.scope(self.execute_redirects(request)).await?;
+        let ttfb = start.elapsed();
+        let t = trace.lock().unwrap_or_else(|e| e.into_inner());
+        resp.extensions_mut().insert(ResponseTrace { start, ttfb, dns: t.dns, connect: t.connect, rate_limit_wait: t.rate_limit_wait, redirects });
+        //The request stays in flight until the response (and its body) is dropped
+        if let Some(p) = permit {
+            resp.extensions_mut().insert(p);
+        }
+        Ok(resp)
+    }
+
+ ///Helper Method: execute_redirects
+ /// Sends the request and follows the redirects allowed by the redirect policy.
+ /// Returns the last response and the URLs that answered with a redirect.
+    async fn execute_redirects(&self, mut request: reqwest::Request) -> Result<(Response, Vec<Url>), Box<dyn std::error::Error>> {
+        let policy = &self.redirect_policy;
+        let mut redirects: Vec<Url> = Vec::new();
+        loop {
+            //None when the body is a stream: the redirect cannot be followed
+            let next = request.try_clone();
+            if let Some(limiter) = &self.rate_limiter {
+                let waited = limiter.acquire(request.url()).await;
+                if !waited.is_zero() {
+                    log_verbose!("execute_redirects", "Request to {} waited {:?} for the rate limiter", redact_url(request.url()), waited);
+                    RequestContext::record(|t| t.rate_limit_wait += waited);
+                }
+            }
+            let circuit = match &self.circuit_breaker {
+                Some(breaker) => Some(breaker.before_request(request.url())?),
+                None => None,
+            };
+            let result = self.client.execute(request).await;
+            if let (Some(breaker), Some(host)) = (&self.circuit_breaker, &circuit) {
+                breaker.after_request(host, result.as_ref().ok().map(|r| r.status().as_u16()));
+            }
+            let resp = result.map_err(|e| e.without_url())?;
+            let status = resp.status().as_u16();
+            let location = match status {
+                301 | 302 | 303 | 307 | 308 => resp.headers().get(header::LOCATION)
+                    .and_then(|l| l.to_str().ok())
+                    .and_then(|l| resp.url().join(l).ok())
+                    .filter(|u| u.scheme() == "http" || u.scheme() == "https"),
+                _ => None,
+            };
+            let (Some(to), Some(mut next)) = (location, next) else {
+                return Ok((resp, redirects));
+            };
+            let from = resp.url().clone();
+            if policy.max_redirects == 0 || (policy.same_host_only && from.host_str() != to.host_str()) {
+                log_verbose!("execute_redirects", "Redirect from {} to {} not followed (redirect policy)", redact_url(&from), redact_url(&to));
+                return Ok((resp, redirects));
+            }
+            if redirects.len() >= policy.max_redirects {
+                return Err(get_error!("execute_redirects", "Too many redirects (more than {}). Last redirect from {} to {}", policy.max_redirects, redact_url(&from), redact_url(&to)).into());
+            }
+
+            let hop = RedirectHop {
+                number: redirects.len() + 1,
+                status_code: status,
+                from_url: redact_url(&from),
+                to_url: redact_url(&to),
+                header: HttpHeaders::from(resp.headers()),
+                cross_origin: !same_origin(&from, &to),
+            };
+            if let Some(inspector) = &policy.inspector && !inspector(&hop) {
+                log_verbose!("execute_redirects", "Redirect from {} to {} stopped by the inspector", hop.from_url, hop.to_url);
+                return Ok((resp, redirects));
             }
+            log_verbose!("execute_redirects", "Following redirect {} ({}) from {} to {}", hop.number, status, hop.from_url, hop.to_url);
+
+            //303 (except HEAD), and 301 / 302 of a POST, continue with GET and without body. 307 and 308 keep both
+            if (status == 303 && next.method() != Method::HEAD) || (matches!(status, 301 | 302) && next.method() == Method::POST) {
+                *next.method_mut() = Method::GET;
+                *next.body_mut() = None;
+                for h in [header::CONTENT_TYPE, header::CONTENT_LENGTH, header::CONTENT_ENCODING, header::TRANSFER_ENCODING] {
+                    next.headers_mut().remove(h);
+                }
+            }
+            if hop.cross_origin && policy.strip_auth_cross_origin {
+                for h in [header::AUTHORIZATION, header::COOKIE, header::PROXY_AUTHORIZATION, header::WWW_AUTHENTICATE] {
+                    next.headers_mut().remove(h);
+                }
+            }
+            *next.url_mut() = to;
+            redirects.push(from);
+            request = next;
         }
     }
 
@@ -318,22 +1077,28 @@ impl HttpClient {
                 "0.0.0.0".to_owned()
             },
         };
+        let mut info = ResponseInfo::from_response(&resp);
 
         if resp.status().is_client_error() || resp.status().is_server_error() {
             log_error!( "extract_response", "ERROR: Failed to get response from {}: {} Status Code: {}", method, url, resp.status() );
-            return HttpResponse {
+            info.finish();
+            HttpResponse {
                 status_code: resp.status().as_u16(),
-                header: convert_headers(resp.headers()),
+                header: HttpHeaders::from(resp.headers()),
                 body: format!( "ERROR: Failed to get response from {}:{} -Error: {}", method, url, resp.status().canonical_reason().unwrap_or("UNKNOWN ERROR!") ),
-                remote_address: ra
-            };
+                remote_address: ra,
+                info,
+            }
         } else {
             let mut full_body = String::new();
             let mut error_count = 0;
             let rstatus = resp.status().as_u16();
-            let rheader = convert_headers(resp.headers());
+            let rheader = HttpHeaders::from(resp.headers());
 
-            if resp.status().is_success() {
+            //No body: HEAD responses (Content-Length describes the resource, not the message), 204 and 304
+            if method == "HEAD" || rstatus == 204 || rstatus == 304 {
+                log_verbose!("extract_response", "{} {} answered {} without body", method, url, rstatus);
+            } else if resp.status().is_success() {
                 let mut read_resp: bool = true;
                 // Process the response body as it's being streamed
                 while read_resp {
@@ -347,17 +1112,19 @@ impl HttpClient {
                         Err(e) => {
                             if error_count > 3{
                                 log_error!("extract_response","Too many errors (>3 times) reading answer body. Stop Executing and return what was collected. Error {}",e);
+                                info.finish();
                                 return HttpResponse {
                                     status_code: resp.status().as_u16(),
-                                    header: convert_headers(resp.headers()),
+                                    header: HttpHeaders::from(resp.headers()),
                                     body: resp.text().await.expect(full_body.as_str() ),
                                         //get_error!("extract_response","ERROR: Failed to get payload from {}:{}",method,url)
                                         //    .as_str(),
                                         //),
                                     remote_address: ra,
+                                    info,
                                 };
                             }
-                            error_count = error_count + 1;
+                            error_count += 1;
                             log_error!("extract_response","Error reading answer body (error count={}). Error {}",error_count,e);                
                         },
                     }
@@ -368,7 +1135,8 @@ impl HttpClient {
                         .as_str(),
                     );
             }
-            return HttpResponse {
+            info.finish();
+            HttpResponse {
                 status_code: rstatus, // resp.status().as_u16(),
                 header: rheader, //Self::convert_headers(resp.headers()),
                 //body: resp.text().await.expect(
@@ -377,21 +1145,24 @@ impl HttpClient {
                 //),
                 body: full_body,
                 remote_address: ra,
-            };
+                info,
+            }
         }
     }
 
 
 }
 
-    ///Helper Method convert_headers: A private method to convert HeaderMap to HashMap.
-    fn convert_headers(headers: &HeaderMap) -> HashMap<String, String> {
-        headers
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or_default().to_string()))
-            .collect()
+///Helper Function: Converts and validates a HashMap of headers.
+fn to_header_map(headers: &HashMap<String, String>) -> Result<HeaderMap, Box<dyn std::error::Error>> {
+    let mut hm = HeaderMap::new();
+    for (key, value) in headers {
+        let (n, v) = parse_header(key, value)?;
+        hm.insert(n, v);
     }
-    
+    Ok(hm)
+}
+
 impl HttpResponse {
 ///The is_error method is used to check if the response is an error:    
     pub fn is_error(&self) -> bool {
@@ -0,0 +1,41 @@
+use std::fmt;
+
+use reqwest::{header::HeaderMap, Method, Request, Url};
+
+use crate::{stream_response::HttpStreamResponse, HttpResponse};
+
+///Middleware: Hooks called by HttpClient around every request (get, head, post, request..., batch and the streaming calls).
+/// Registered in order with HttpClient::add_middleware or HttpClientConfig::middleware. Errors of the hooks stop the request
+/// and are returned to the caller.
+/// - on_request: Called in registration order before authentication and signing are added. Can modify the request, or return
+///   a response to short-circuit it (e.g. a cached response): the request is not sent and the following middleware are skipped.
+/// - on_response: Called in reverse order with the response (its body read) of the middleware whose on_request ran
+///   (not the one that short-circuited). Can modify it.
+/// - on_stream_response: Same as on_response for the streaming calls (post_stream and request_stream). The body is not read yet.
+pub trait Middleware: Send + Sync + fmt::Debug {
+    fn on_request(&self, _request: &mut Request) -> Result<Option<HttpResponse>, Box<dyn std::error::Error>> {
+        Ok(None)
+    }
+
+    fn on_response(&self, _request: &MiddlewareRequest, _response: &mut HttpResponse) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    fn on_stream_response(&self, _request: &MiddlewareRequest, _response: &mut HttpStreamResponse) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+}
+
+///MiddlewareRequest: The request a response answers, as sent after the on_request hooks (without authentication and signature).
+#[derive(Clone, Debug)]
+pub struct MiddlewareRequest {
+    pub method: Method,
+    pub url: Url,
+    pub headers: HeaderMap,
+}
+
+impl MiddlewareRequest {
+    pub(crate) fn from_request(request: &Request) -> Self {
+        Self { method: request.method().clone(), url: request.url().clone(), headers: request.headers().clone() }
+    }
+}
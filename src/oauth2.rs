@@ -0,0 +1,182 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bt_logger::{get_error, log_verbose, log_warning};
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+const DEFAULT_REFRESH_MARGIN_SECS: u64 = 30;
+
+///OAuth2Config: Token endpoint and credentials used by OAuth2TokenProvider.
+/// token_url: URL of the token endpoint.
+/// client_id / client_secret: Client credentials.
+/// scope: Optional space separated list of scopes.
+/// refresh_token: Optional refresh token. When present, it is used (grant_type=refresh_token) before client credentials.
+/// credentials_in_body: If true, client_id and client_secret are sent in the form body (client_secret_post).
+///                      By default they are sent with HTTP Basic authentication (client_secret_basic).
+/// refresh_margin: A cached token is renewed this long before it expires. Default 30 seconds.
+#[derive(Clone)]
+pub struct OAuth2Config {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scope: Option<String>,
+    pub refresh_token: Option<String>,
+    pub credentials_in_body: bool,
+    pub refresh_margin: Duration,
+}
+
+impl OAuth2Config {
+    ///Client credentials grant configuration with default options.
+    pub fn client_credentials(token_url: &str, client_id: &str, client_secret: &str, scope: Option<&str>) -> Self {
+        Self {
+            token_url: token_url.to_owned(),
+            client_id: client_id.to_owned(),
+            client_secret: client_secret.to_owned(),
+            scope: scope.map(|s| s.to_owned()),
+            refresh_token: None,
+            credentials_in_body: false,
+            refresh_margin: Duration::from_secs(DEFAULT_REFRESH_MARGIN_SECS),
+        }
+    }
+}
+
+impl fmt::Debug for OAuth2Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuth2Config")
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"***")
+            .field("scope", &self.scope)
+            .field("refresh_token", &self.refresh_token.as_ref().map(|_| "***"))
+            .field("credentials_in_body", &self.credentials_in_body)
+            .field("refresh_margin", &self.refresh_margin)
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+    refresh_token: Option<String>,
+}
+
+struct CachedToken {
+    access_token: String,
+    expires_at: Option<Instant>,
+}
+
+struct TokenState {
+    token: Option<CachedToken>,
+    refresh_token: Option<String>,
+    fetch_count: u64,
+}
+
+///OAuth2TokenProvider: Fetches access tokens from an OAuth2 token endpoint and caches them until shortly before they expire.
+/// Cloning the provider shares the cache. Concurrent requests wait for a single token request instead of each fetching one.
+/// Used through HttpAuth::oauth2. The HttpClient adds "Authorization: Bearer <token>" and, when the server answers 401,
+/// invalidates the token and retries the request once with a new one.
+#[derive(Clone)]
+pub struct OAuth2TokenProvider {
+    config: Arc<OAuth2Config>,
+    state: Arc<Mutex<TokenState>>,
+}
+
+impl fmt::Debug for OAuth2TokenProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OAuth2TokenProvider {{ config: {:?} }}", self.config)
+    }
+}
+
+impl OAuth2TokenProvider {
+    pub fn new(config: OAuth2Config) -> Self {
+        let refresh_token = config.refresh_token.clone();
+        Self {
+            config: Arc::new(config),
+            state: Arc::new(Mutex::new(TokenState { token: None, refresh_token, fetch_count: 0 })),
+        }
+    }
+
+    ///Returns a valid access token, fetching a new one if there is no cached token or it is about to expire.
+    pub async fn get_token(&self, client: &Client) -> Result<String, Box<dyn std::error::Error>> {
+        let mut state = self.state.lock().await;
+        if let Some(t) = &state.token
+            && t.expires_at.is_none_or(|exp| Instant::now() + self.config.refresh_margin < exp) {
+                return Ok(t.access_token.clone());
+        }
+        self.fetch_token(client, &mut state).await
+    }
+
+    ///Drops the cached token if it is still the given one (another request may have renewed it already).
+    pub async fn invalidate(&self, access_token: &str) {
+        let mut state = self.state.lock().await;
+        if state.token.as_ref().is_some_and(|t| t.access_token == access_token) {
+            state.token = None;
+        }
+    }
+
+    ///Number of tokens fetched from the token endpoint since the provider was created.
+    pub async fn get_fetch_count(&self) -> u64 {
+        self.state.lock().await.fetch_count
+    }
+
+    ///Helper Method: Requests a token using the refresh token (if any) and falls back to client credentials.
+    async fn fetch_token(&self, client: &Client, state: &mut TokenState) -> Result<String, Box<dyn std::error::Error>> {
+        let mut response = None;
+        if let Some(refresh_token) = state.refresh_token.clone() {
+            match self.request_token(client, &[("grant_type", "refresh_token"), ("refresh_token", &refresh_token)]).await {
+                Ok(r) => response = Some(r),
+                Err(e) => {
+                    log_warning!("fetch_token", "Refresh token rejected, using client credentials. Error: {}", e);
+                    state.refresh_token = None;
+                }
+            }
+        }
+        let response = match response {
+            Some(r) => r,
+            None => self.request_token(client, &[("grant_type", "client_credentials")]).await?,
+        };
+
+        state.fetch_count += 1;
+        if response.refresh_token.is_some() {
+            state.refresh_token = response.refresh_token;
+        }
+        state.token = Some(CachedToken {
+            access_token: response.access_token.clone(),
+            expires_at: response.expires_in.map(|s| Instant::now() + Duration::from_secs(s)),
+        });
+        log_verbose!("fetch_token", "New OAuth2 token from {} (expires in {:?} s)", self.config.token_url, response.expires_in);
+        Ok(response.access_token)
+    }
+
+    async fn request_token(&self, client: &Client, grant: &[(&str, &str)]) -> Result<TokenResponse, Box<dyn std::error::Error>> {
+        let mut form: Vec<(&str, &str)> = grant.to_vec();
+        if let Some(scope) = &self.config.scope {
+            form.push(("scope", scope));
+        }
+        let mut request = client.post(&self.config.token_url);
+        if self.config.credentials_in_body {
+            form.push(("client_id", &self.config.client_id));
+            form.push(("client_secret", &self.config.client_secret));
+        } else {
+            request = request.basic_auth(&self.config.client_id, Some(&self.config.client_secret));
+        }
+
+        let resp = match request.form(&form).send().await {
+            Ok(r) => r,
+            Err(e) => return Err(get_error!("request_token", "Failed to get OAuth2 token from {}. Error: {}", self.config.token_url, e.without_url()).into()),
+        };
+        if !resp.status().is_success() {
+            return Err(get_error!("request_token", "OAuth2 token endpoint {} answered {}", self.config.token_url, resp.status()).into());
+        }
+        match resp.json::<TokenResponse>().await {
+            Ok(t) => Ok(t),
+            Err(e) => Err(get_error!("request_token", "Invalid OAuth2 token response from {}. Error: {}", self.config.token_url, e.without_url()).into()),
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+
+use bt_logger::get_error;
+use reqwest::{NoProxy, Proxy, Url};
+
+use crate::auth::redact_url;
+
+///ProxyTarget: Requests sent through a proxy.
+/// ALL: Every request. HTTP: Only http:// URLs. HTTPS: Only https:// URLs.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ProxyTarget {
+    #[default]
+    ALL,
+    HTTP,
+    HTTPS,
+}
+
+///ProxyConfig: A proxy used by HttpClient (HttpClientConfig::proxies).
+/// url: Proxy URL: http://host:port, https://host:port (TLS to the proxy), socks5://host:port (local DNS resolution)
+///      or socks5h://host:port (the proxy resolves host names). Credentials can also be given in the URL (user:password@host).
+/// target: Requests sent through the proxy (ProxyTarget).
+/// username / password: Proxy credentials (Proxy-Authorization: Basic for HTTP proxies, username / password authentication for SOCKS5).
+/// no_proxy: Hosts that bypass the proxy, comma separated, same format as the NO_PROXY environment variable:
+///           domain names (also match subdomains, ".example.com" is the same as "example.com"), IP addresses,
+///           subnets ("192.168.1.0/24") or "*" for every host.
+#[derive(Clone, Default)]
+pub struct ProxyConfig {
+    pub url: String,
+    pub target: ProxyTarget,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+impl fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("url", &Url::parse(&self.url).map(|u| redact_url(&u)).unwrap_or_else(|_| self.url.clone()))
+            .field("target", &self.target)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("no_proxy", &self.no_proxy)
+            .finish()
+    }
+}
+
+impl ProxyConfig {
+    ///Proxy for every request.
+    pub fn new(url: &str) -> Self {
+        Self { url: url.to_owned(), ..Default::default() }
+    }
+
+    ///Sets the target requests (chainable).
+    pub fn target(mut self, target: ProxyTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    ///Sets the proxy credentials (chainable).
+    pub fn credentials(mut self, username: &str, password: &str) -> Self {
+        self.username = Some(username.to_owned());
+        self.password = Some(password.to_owned());
+        self
+    }
+
+    ///Sets the hosts that bypass the proxy (chainable).
+    pub fn no_proxy(mut self, no_proxy: &str) -> Self {
+        self.no_proxy = Some(no_proxy.to_owned());
+        self
+    }
+
+    ///Converts to a reqwest Proxy. Returns an error if the URL is invalid or the scheme is not http, https, socks5 or socks5h.
+    pub(crate) fn to_proxy(&self) -> Result<Proxy, Box<dyn std::error::Error>> {
+        let url = match Url::parse(&self.url) {
+            Ok(u) => u,
+            Err(e) => return Err(get_error!("to_proxy", "Invalid proxy URL. Error: {}", e).into()),
+        };
+        if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+            return Err(get_error!("to_proxy", "Unsupported proxy scheme '{}'. Use http, https, socks5 or socks5h", url.scheme()).into());
+        }
+        let proxy = match self.target {
+            ProxyTarget::ALL => Proxy::all(url),
+            ProxyTarget::HTTP => Proxy::http(url),
+            ProxyTarget::HTTPS => Proxy::https(url),
+        };
+        let mut proxy = match proxy {
+            Ok(p) => p,
+            Err(e) => return Err(get_error!("to_proxy", "Invalid proxy. Error: {}", e).into()),
+        };
+        if let Some(username) = &self.username {
+            proxy = proxy.basic_auth(username, self.password.as_deref().unwrap_or_default());
+        }
+        Ok(proxy.no_proxy(self.no_proxy.as_deref().and_then(NoProxy::from_string)))
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+
+use bt_logger::get_error;
+use serde::{
+    ser::{self, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple},
+    Serialize, Serializer,
+};
+use serde_urlencoded::ser::Error as EncodeError;
+use url::form_urlencoded;
+
+///QueryParams: Ordered query parameters. Names can repeat (?tag=a&tag=b) and the order given is the order sent,
+/// which matters for APIs that sign the query string or depend on parameter order.
+/// Built with add / push, from a list of pairs, from any Serialize struct (from_serialize) or from a HashMap (sorted by name).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueryParams {
+    pairs: Vec<(String, String)>,
+}
+
+impl QueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    ///Adds a parameter at the end (chainable).
+    pub fn add(mut self, name: &str, value: &str) -> Self {
+        self.push(name, value);
+        self
+    }
+
+    ///Adds a parameter at the end.
+    pub fn push(&mut self, name: &str, value: &str) {
+        self.pairs.push((name.to_owned(), value.to_owned()));
+    }
+
+    ///Builds the parameters from a Serialize value (usually a struct) in field order. Values are encoded like serde_urlencoded
+    /// (the format of reqwest RequestBuilder::query). Sequences become repeated parameters, None fields are skipped.
+    /// Nested structs and maps are not supported.
+    pub fn from_serialize<T: Serialize>(value: &T) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut params = Self::new();
+        match value.serialize(PairsSerializer { params: &mut params }) {
+            Ok(()) => Ok(params),
+            Err(e) => Err(get_error!("from_serialize", "Invalid query parameters. Error: {}", e).into()),
+        }
+    }
+
+    ///Returns every value of the parameter, in order.
+    pub fn get_all(&self, name: &str) -> Vec<&str> {
+        self.pairs.iter().filter(|(n, _)| n == name).map(|(_, v)| v.as_str()).collect()
+    }
+
+    ///Removes the parameter and returns its values, in order.
+    pub fn remove(&mut self, name: &str) -> Vec<String> {
+        let mut removed = Vec::new();
+        self.pairs.retain(|(n, v)| {
+            if n == name {
+                removed.push(v.clone());
+                return false;
+            }
+            true
+        });
+        removed
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for (n, _) in &self.pairs {
+            if !names.contains(&n.as_str()) {
+                names.push(n);
+            }
+        }
+        names
+    }
+
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(|(n, v)| (n.as_str(), v.as_str()))
+    }
+
+    ///Returns the encoded query string (application/x-www-form-urlencoded) without "?".
+    pub fn to_query_string(&self) -> String {
+        form_urlencoded::Serializer::new(String::new()).extend_pairs(&self.pairs).finish()
+    }
+
+    pub(crate) fn pairs(&self) -> &[(String, String)] {
+        &self.pairs
+    }
+}
+
+impl From<Vec<(String, String)>> for QueryParams {
+    fn from(pairs: Vec<(String, String)>) -> Self {
+        Self { pairs }
+    }
+}
+
+impl From<Vec<(&str, &str)>> for QueryParams {
+    fn from(pairs: Vec<(&str, &str)>) -> Self {
+        QueryParams::from(pairs.as_slice())
+    }
+}
+
+impl From<&[(&str, &str)]> for QueryParams {
+    fn from(pairs: &[(&str, &str)]) -> Self {
+        Self { pairs: pairs.iter().map(|(n, v)| (n.to_string(), v.to_string())).collect() }
+    }
+}
+
+///HashMap has no order: parameters are sorted by name so the query string is always the same.
+impl From<HashMap<String, String>> for QueryParams {
+    fn from(map: HashMap<String, String>) -> Self {
+        let mut pairs: Vec<(String, String)> = map.into_iter().collect();
+        pairs.sort();
+        Self { pairs }
+    }
+}
+
+///Helper Function: Adds the name / value pairs of a field. Sequences add one pair per item.
+fn push_value<T: Serialize + ?Sized>(params: &mut QueryParams, name: &str, value: &T) -> Result<(), EncodeError> {
+    match serde_urlencoded::to_string([(name, value)]) {
+        Ok(encoded) => {
+            params.pairs.extend(form_urlencoded::parse(encoded.as_bytes()).into_owned());
+            Ok(())
+        }
+        Err(_) => value.serialize(SeqSerializer { params, name }),
+    }
+}
+
+///Helper Function: Text of a map key.
+fn to_text<T: Serialize + ?Sized>(value: &T) -> Result<String, EncodeError> {
+    let encoded = serde_urlencoded::to_string([("", value)])?;
+    match form_urlencoded::parse(encoded.as_bytes()).next() {
+        Some((_, text)) => Ok(text.into_owned()),
+        None => Err(unsupported()),
+    }
+}
+
+fn unsupported() -> EncodeError {
+    ser::Error::custom("query parameters must be a struct or a map of values or sequences of values (no nested structs or maps)")
+}
+
+///Unsupported methods of the helper serializers.
+macro_rules! unsupported {
+    ($($method:ident($($arg:ty),*)),* $(,)?) => {
+        $(fn $method(self, $(_: $arg),*) -> Result<Self::Ok, Self::Error> { Err(unsupported()) })*
+    };
+}
+
+///Helper Serializer: Top level of from_serialize. Fields in declaration order (structs) or iteration order (maps).
+struct PairsSerializer<'a> {
+    params: &'a mut QueryParams,
+}
+
+struct PairsMap<'a> {
+    params: &'a mut QueryParams,
+    key: Option<String>,
+}
+
+impl<'a> Serializer for PairsSerializer<'a> {
+    type Ok = ();
+    type Error = EncodeError;
+    type SerializeSeq = Impossible<(), EncodeError>;
+    type SerializeTuple = Impossible<(), EncodeError>;
+    type SerializeTupleStruct = Impossible<(), EncodeError>;
+    type SerializeTupleVariant = Impossible<(), EncodeError>;
+    type SerializeMap = PairsMap<'a>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), EncodeError>;
+
+    unsupported!(serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32), serialize_i64(i64),
+        serialize_u8(u8), serialize_u16(u16), serialize_u32(u32), serialize_u64(u64), serialize_f32(f32), serialize_f64(f64),
+        serialize_char(char), serialize_str(&str), serialize_bytes(&[u8]), serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str));
+
+    fn serialize_none(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_unit(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<(), EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, EncodeError> {
+        Ok(PairsMap { params: self.params, key: None })
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, EncodeError> {
+        Err(unsupported())
+    }
+}
+
+impl SerializeStruct for PairsSerializer<'_> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, name: &'static str, value: &T) -> Result<(), EncodeError> {
+        push_value(self.params, name, value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+impl SerializeMap for PairsMap<'_> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), EncodeError> {
+        self.key = Some(to_text(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        let key = self.key.take().unwrap_or_default();
+        push_value(self.params, &key, value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+///Helper Serializer: A field that serde_urlencoded cannot encode. Only sequences of values are accepted (repeated parameters).
+struct SeqSerializer<'a> {
+    params: &'a mut QueryParams,
+    name: &'a str,
+}
+
+impl<'a> Serializer for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = EncodeError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Impossible<(), EncodeError>;
+    type SerializeTupleVariant = Impossible<(), EncodeError>;
+    type SerializeMap = Impossible<(), EncodeError>;
+    type SerializeStruct = Impossible<(), EncodeError>;
+    type SerializeStructVariant = Impossible<(), EncodeError>;
+
+    unsupported!(serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32), serialize_i64(i64),
+        serialize_u8(u8), serialize_u16(u16), serialize_u32(u32), serialize_u64(u64), serialize_f32(f32), serialize_f64(f64),
+        serialize_char(char), serialize_str(&str), serialize_bytes(&[u8]), serialize_none(), serialize_unit(),
+        serialize_unit_struct(&'static str), serialize_unit_variant(&'static str, u32, &'static str));
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _: &'static str, value: &T) -> Result<(), EncodeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _: &'static str, _: u32, _: &'static str, _: &T) -> Result<(), EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, EncodeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeTupleStruct, EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_tuple_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeTupleVariant, EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, EncodeError> {
+        Err(unsupported())
+    }
+
+    fn serialize_struct_variant(self, _: &'static str, _: u32, _: &'static str, _: usize) -> Result<Self::SerializeStructVariant, EncodeError> {
+        Err(unsupported())
+    }
+}
+
+impl SerializeSeq for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        let encoded = serde_urlencoded::to_string([(self.name, value)]).map_err(|_| unsupported())?;
+        self.params.pairs.extend(form_urlencoded::parse(encoded.as_bytes()).into_owned());
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
+
+impl SerializeTuple for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), EncodeError> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
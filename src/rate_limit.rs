@@ -0,0 +1,159 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use reqwest::Url;
+
+use crate::host::host_key;
+
+///RateLimit: Token bucket. requests_per_second tokens are added every second up to burst. Each request takes one token.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    pub requests_per_second: f64,
+    pub burst: u32,
+}
+
+impl RateLimit {
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        Self { requests_per_second, burst }
+    }
+}
+
+///RateLimitKeyFn: Returns the bucket key of a request URL.
+pub type RateLimitKeyFn = Arc<dyn Fn(&Url) -> String + Send + Sync>;
+
+///RateLimiterConfig: Options of a RateLimiter.
+/// default_limit: Limit of the keys without their own limit. None: those requests are not limited.
+/// limits: Limit per key, lowercase (e.g. "api.example.com" or "api.example.com:8443").
+/// key: Function returning the key of a request. None uses the host, plus ":port" when the URL has a non default port.
+#[derive(Clone, Default)]
+pub struct RateLimiterConfig {
+    pub default_limit: Option<RateLimit>,
+    pub limits: HashMap<String, RateLimit>,
+    pub key: Option<RateLimitKeyFn>,
+}
+
+impl fmt::Debug for RateLimiterConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimiterConfig")
+            .field("default_limit", &self.default_limit)
+            .field("limits", &self.limits)
+            .field("key", &self.key.is_some())
+            .finish()
+    }
+}
+
+impl RateLimiterConfig {
+    ///Same limit for every key.
+    pub fn new(default_limit: RateLimit) -> Self {
+        Self { default_limit: Some(default_limit), ..Default::default() }
+    }
+
+    ///Sets the limit of a key (chainable).
+    pub fn limit(mut self, key: &str, limit: RateLimit) -> Self {
+        self.limits.insert(key.to_lowercase(), limit);
+        self
+    }
+
+    ///Sets the function returning the key of a request (chainable).
+    pub fn key(mut self, key: impl Fn(&Url) -> String + Send + Sync + 'static) -> Self {
+        self.key = Some(Arc::new(key));
+        self
+    }
+}
+
+///RateLimitStats: Requests that went through a key of the RateLimiter.
+/// requests: Number of requests. delayed: Requests that had to wait.
+/// total_wait / max_wait: Total and longest time requests waited.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RateLimitStats {
+    pub requests: u64,
+    pub delayed: u64,
+    pub total_wait: Duration,
+    pub max_wait: Duration,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    //Negative when requests are waiting for tokens (reserved in arrival order)
+    tokens: f64,
+    updated: Instant,
+    stats: RateLimitStats,
+}
+
+///RateLimiter: Client side rate limiting. Requests over the limit wait for their turn instead of failing.
+/// Shared by the clients and sessions it is set on (HttpClientConfig::rate_limiter or HttpClient::set_rate_limiter).
+/// Every request sent counts, including redirects and authentication retries.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimiterConfig,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimiterConfig) -> Self {
+        Self { config, buckets: Mutex::new(HashMap::new()) }
+    }
+
+    ///Returns the key of the URL.
+    pub fn key_for(&self, url: &Url) -> String {
+        match &self.config.key {
+            Some(key) => key(url),
+            None => host_key(url),
+        }
+    }
+
+    ///Waits until the request to the URL is allowed. Returns the time waited.
+    pub async fn acquire(&self, url: &Url) -> Duration {
+        let wait = self.reserve(&self.key_for(url));
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        wait
+    }
+
+    ///Statistics of a key. None if the key has no limit or no request used it.
+    pub fn get_stats(&self, key: &str) -> Option<RateLimitStats> {
+        self.lock().get(&key.to_lowercase()).map(|b| b.stats.clone())
+    }
+
+    ///Statistics of every key.
+    pub fn get_all_stats(&self) -> HashMap<String, RateLimitStats> {
+        self.lock().iter().map(|(k, b)| (k.clone(), b.stats.clone())).collect()
+    }
+
+    ///Helper Method: Takes a token (a future one if none is left) and returns how long to wait for it.
+    fn reserve(&self, key: &str) -> Duration {
+        let key = key.to_lowercase();
+        let Some(limit) = self.config.limits.get(&key).or(self.config.default_limit.as_ref()).copied() else {
+            return Duration::ZERO;
+        };
+        if limit.requests_per_second <= 0.0 {
+            return Duration::ZERO;
+        }
+        let burst = f64::from(limit.burst.max(1));
+        let now = Instant::now();
+        let mut buckets = self.lock();
+        let bucket = buckets.entry(key).or_insert_with(|| Bucket { tokens: burst, updated: now, stats: RateLimitStats::default() });
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.requests_per_second).min(burst);
+        bucket.updated = now;
+        bucket.tokens -= 1.0;
+
+        let wait = if bucket.tokens < 0.0 { Duration::from_secs_f64(-bucket.tokens / limit.requests_per_second) } else { Duration::ZERO };
+        bucket.stats.requests += 1;
+        if !wait.is_zero() {
+            bucket.stats.delayed += 1;
+            bucket.stats.total_wait += wait;
+            bucket.stats.max_wait = bucket.stats.max_wait.max(wait);
+        }
+        wait
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Bucket>> {
+        self.buckets.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
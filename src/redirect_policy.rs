@@ -0,0 +1,96 @@
+use std::{fmt, sync::Arc};
+
+use reqwest::Url;
+
+use crate::http_headers::HttpHeaders;
+
+///Maximum number of redirects followed by default (same as browsers and reqwest).
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+///RedirectHop: A redirect answered by the server, given to the RedirectPolicy inspector before it is followed.
+/// number: 1 for the first redirect, 2 for the second...
+/// status_code: Redirect status (301, 302, 303, 307 or 308).
+/// from_url: URL that answered with the redirect (password redacted).
+/// to_url: URL of the Location header, resolved against from_url (password redacted).
+/// header: Headers of the redirect response.
+/// cross_origin: True when to_url has a different scheme, host or port.
+#[derive(Clone, Debug)]
+pub struct RedirectHop {
+    pub number: usize,
+    pub status_code: u16,
+    pub from_url: String,
+    pub to_url: String,
+    pub header: HttpHeaders,
+    pub cross_origin: bool,
+}
+
+///RedirectInspector: Called for every redirect. Returns true to follow it or false to stop and return the redirect response.
+pub type RedirectInspector = Arc<dyn Fn(&RedirectHop) -> bool + Send + Sync>;
+
+///RedirectPolicy: How HttpClient follows redirects. Set it on the client (HttpClientConfig::redirect_policy, HttpClient::set_redirect_policy)
+/// or for some requests only (HttpClient::with_redirect_policy).
+/// max_redirects: Maximum number of redirects. 0 does not follow redirects (the 3xx response is returned). More redirects is an error.
+/// same_host_only: Stops (returns the 3xx response) when the redirect goes to another host.
+/// strip_auth_cross_origin: Removes Authorization, Cookie, Proxy-Authorization and WWW-Authenticate headers when the redirect
+///                          goes to another origin (scheme, host or port). Default true.
+/// inspector: Optional function called for every redirect (RedirectInspector).
+/// 301 and 302 redirects of a POST and 303 redirects are followed with GET and without body. 307 and 308 keep the method and the body.
+/// A URL can be visited again (e.g. login flows): redirect loops end with the max_redirects error.
+#[derive(Clone)]
+pub struct RedirectPolicy {
+    pub max_redirects: usize,
+    pub same_host_only: bool,
+    pub strip_auth_cross_origin: bool,
+    pub inspector: Option<RedirectInspector>,
+}
+
+impl Default for RedirectPolicy {
+    fn default() -> Self {
+        Self {
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            same_host_only: false,
+            strip_auth_cross_origin: true,
+            inspector: None,
+        }
+    }
+}
+
+impl fmt::Debug for RedirectPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedirectPolicy")
+            .field("max_redirects", &self.max_redirects)
+            .field("same_host_only", &self.same_host_only)
+            .field("strip_auth_cross_origin", &self.strip_auth_cross_origin)
+            .field("inspector", &self.inspector.is_some())
+            .finish()
+    }
+}
+
+impl RedirectPolicy {
+    ///Does not follow redirects: the 3xx response is returned.
+    pub fn none() -> Self {
+        Self::limited(0)
+    }
+
+    ///Follows up to max_redirects redirects.
+    pub fn limited(max_redirects: usize) -> Self {
+        Self { max_redirects, ..Default::default() }
+    }
+
+    ///Only follows redirects to the same host (chainable).
+    pub fn same_host(mut self) -> Self {
+        self.same_host_only = true;
+        self
+    }
+
+    ///Sets the function called for every redirect (chainable).
+    pub fn inspect(mut self, inspector: impl Fn(&RedirectHop) -> bool + Send + Sync + 'static) -> Self {
+        self.inspector = Some(Arc::new(inspector));
+        self
+    }
+}
+
+///Helper Function: True when both URLs have the same scheme, host and port.
+pub(crate) fn same_origin(a: &Url, b: &Url) -> bool {
+    a.scheme() == b.scheme() && a.host_str() == b.host_str() && a.port_or_known_default() == b.port_or_known_default()
+}
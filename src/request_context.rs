@@ -0,0 +1,99 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::cookie_jar::CookieJar;
+
+tokio::task_local! {
+    ///Context of the HttpClient request being executed by the current task.
+    static CONTEXT: RequestContext;
+}
+
+///RequestTrace: DNS and connection times of a request (None when a pooled connection was reused) and time waited for the rate limiter.
+#[derive(Debug, Default)]
+pub(crate) struct RequestTrace {
+    pub dns: Option<Duration>,
+    pub connect: Option<Duration>,
+    pub rate_limit_wait: Duration,
+}
+
+///RequestContext: Per request state seen by the hooks of the shared reqwest Client (cookie provider, DNS resolver
+/// and connector), which are called from the task that executes the request.
+#[derive(Clone)]
+pub(crate) struct RequestContext {
+    pub cookie_jar: Option<Arc<CookieJar>>,
+    pub trace: Arc<Mutex<RequestTrace>>,
+}
+
+impl RequestContext {
+    pub(crate) fn new(cookie_jar: Option<Arc<CookieJar>>) -> Self {
+        Self { cookie_jar, trace: Arc::new(Mutex::new(RequestTrace::default())) }
+    }
+
+    ///Runs the future (the reqwest execute call) with this context.
+    pub(crate) async fn scope<F: Future>(self, f: F) -> F::Output {
+        CONTEXT.scope(self, f).await
+    }
+
+    ///Context of the current request. None outside HttpClient requests (e.g. background connections).
+    pub(crate) fn current() -> Option<RequestContext> {
+        CONTEXT.try_with(|c| c.clone()).ok()
+    }
+
+    ///Updates the trace of the current request, if any.
+    pub(crate) fn record(f: impl FnOnce(&mut RequestTrace)) {
+        if let Some(ctx) = Self::current() {
+            f(&mut ctx.trace.lock().unwrap_or_else(|e| e.into_inner()));
+        }
+    }
+}
+
+///ConnectTimerLayer: Connector layer that records how long opening a new connection took (DNS, TCP and TLS).
+#[derive(Clone)]
+pub(crate) struct ConnectTimerLayer;
+
+impl<S> Layer<S> for ConnectTimerLayer {
+    type Service = ConnectTimer<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectTimer { inner }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ConnectTimer<S> {
+    inner: S,
+}
+
+impl<S, R> Service<R> for ConnectTimer<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let start = Instant::now();
+        let connecting = self.inner.call(request);
+        Box::pin(async move {
+            let result = connecting.await;
+            let elapsed = start.elapsed();
+            //The DNS time is recorded by the resolver. connect is the rest: TCP connection and TLS handshake.
+            RequestContext::record(|t| t.connect = Some(elapsed.saturating_sub(t.dns.unwrap_or_default())));
+            result
+        })
+    }
+}
@@ -0,0 +1,131 @@
+use std::time::{Duration, Instant};
+
+use reqwest::{Response, Url, Version};
+
+use crate::auth::redact_url;
+
+///ResponseTrace: Timings and redirects recorded while executing a request. Attached to the reqwest Response.
+#[derive(Clone, Debug)]
+pub(crate) struct ResponseTrace {
+    pub start: Instant,
+    pub ttfb: Duration,
+    pub dns: Option<Duration>,
+    pub connect: Option<Duration>,
+    pub rate_limit_wait: Duration,
+    pub redirects: Vec<Url>,
+}
+
+///ResponseTimings: Where the time of a request was spent.
+/// dns: DNS lookup. None when no lookup was done (reused connection or IP address in the URL).
+/// connect: Opening the connection after DNS: TCP connection plus TLS handshake for https. None when a pooled connection was reused.
+/// ttfb: Time to first byte: from sending the request to receiving the response headers (including redirects, DNS and connect).
+/// total: From sending the request until the body was read (equal to ttfb for streamed responses until finished).
+/// reused_connection: True when the final request used an already open connection.
+/// rate_limit_wait: Time waited for the rate limiter (included in ttfb).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResponseTimings {
+    pub dns: Option<Duration>,
+    pub connect: Option<Duration>,
+    pub ttfb: Duration,
+    pub total: Duration,
+    pub reused_connection: bool,
+    pub rate_limit_wait: Duration,
+}
+
+///CacheStatus: Whether a response came from the HTTP cache (http_cache::HttpCache).
+/// NONE: No cache, or the request was not cacheable. MISS: Sent to the server (and stored if cacheable).
+/// HIT: Served from the cache without contacting the server. REVALIDATED: Stored response confirmed by the server (304 Not Modified).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CacheStatus {
+    #[default]
+    NONE,
+    MISS,
+    HIT,
+    REVALIDATED,
+}
+
+///ResponseInfo: Metadata of an HTTP response.
+/// final_url: URL that answered after following redirects (password redacted).
+/// version: HTTP version, e.g. "HTTP/1.1" or "HTTP/2.0".
+/// remote_port: Port of the server. None when unknown.
+/// redirects: URLs that answered with a redirect, in order (password redacted). Empty when there was no redirect.
+/// content_length: Content-Length of the response. None when unknown (e.g. chunked responses).
+/// timings: Timing breakdown (ResponseTimings).
+/// cache_status: Whether the response was served from the HTTP cache (CacheStatus).
+#[derive(Clone, Debug, Default)]
+pub struct ResponseInfo {
+    pub final_url: String,
+    pub version: String,
+    pub remote_port: Option<u16>,
+    pub redirects: Vec<String>,
+    pub content_length: Option<u64>,
+    pub timings: ResponseTimings,
+    pub cache_status: CacheStatus,
+    start: Option<Instant>,
+}
+
+impl ResponseInfo {
+    ///Collects the metadata of the response. Timings and redirects are only known for responses of HttpClient requests.
+    pub(crate) fn from_response(resp: &Response) -> Self {
+        let mut info = Self {
+            final_url: redact_url(resp.url()),
+            version: version_to_string(resp.version()),
+            remote_port: resp.remote_addr().map(|a| a.port()),
+            redirects: Vec::new(),
+            content_length: resp.content_length(),
+            timings: ResponseTimings::default(),
+            cache_status: CacheStatus::NONE,
+            start: None,
+        };
+        if let Some(trace) = resp.extensions().get::<ResponseTrace>() {
+            info.redirects = trace.redirects.iter().map(redact_url).collect();
+            info.timings = ResponseTimings {
+                dns: trace.dns,
+                connect: trace.connect,
+                ttfb: trace.ttfb,
+                total: trace.ttfb,
+                reused_connection: trace.connect.is_none(),
+                rate_limit_wait: trace.rate_limit_wait,
+            };
+            info.start = Some(trace.start);
+        }
+        info
+    }
+
+    ///Metadata of a stored response served by the HTTP cache. No timings: nothing was sent.
+    pub(crate) fn from_cache(final_url: &str, version: &str, content_length: Option<u64>, cache_status: CacheStatus) -> Self {
+        Self {
+            final_url: final_url.to_owned(),
+            version: version.to_owned(),
+            content_length,
+            cache_status,
+            ..Default::default()
+        }
+    }
+
+    ///Every URL requested, in order: the redirects followed and the final URL.
+    pub fn visited_urls(&self) -> Vec<String> {
+        let mut urls = self.redirects.clone();
+        urls.push(self.final_url.clone());
+        urls
+    }
+
+    ///Sets the total time once the body has been read.
+    pub(crate) fn finish(&mut self) {
+        if let Some(start) = self.start {
+            self.timings.total = start.elapsed();
+        }
+    }
+}
+
+fn version_to_string(version: Version) -> String {
+    match version {
+        Version::HTTP_09 => "HTTP/0.9",
+        Version::HTTP_10 => "HTTP/1.0",
+        Version::HTTP_11 => "HTTP/1.1",
+        Version::HTTP_2 => "HTTP/2.0",
+        Version::HTTP_3 => "HTTP/3.0",
+        _ => "UNKNOWN",
+    }
+    .to_owned()
+}
@@ -0,0 +1,198 @@
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bt_logger::get_error;
+use hmac::{Hmac, Mac};
+use reqwest::{
+    header::{HeaderName, HeaderValue},
+    Request,
+};
+use sha2::Sha256;
+
+use crate::{auth::to_hex, http_headers::HttpHeaders};
+
+///RequestSigner: Hook called by HttpClient right before a request is sent (after headers and authentication are added,
+/// and again for each automatic retry). Implementations add or modify headers (e.g. a signature) of the final request.
+/// Built-in implementations: HmacSigner and sigv4::SigV4Signer.
+pub trait RequestSigner: Send + Sync + fmt::Debug {
+    fn sign(&self, request: &mut Request) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+///SignatureEncoding: How the HMAC bytes are written in the signature header.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SignatureEncoding {
+    Hex,
+    Base64,
+}
+
+///HmacSignerConfig: HMAC-SHA256 signature scheme.
+/// The signed string is: METHOD + separator + path?query + separator + timestamp + separator + body
+/// secret: Shared secret.
+/// signature_header: Header with the signature. Default "X-Signature".
+/// timestamp_header: Header with the timestamp (Unix seconds). Default "X-Timestamp".
+/// key_id: Optional key identifier sent in key_id_header (default "X-Key-Id").
+/// signature_prefix: Optional prefix of the header value, e.g. "sha256=". Default none.
+/// encoding: Hex (default) or Base64.
+/// separator: Separator between the signed components. Default "\n".
+#[derive(Clone)]
+pub struct HmacSignerConfig {
+    pub secret: Vec<u8>,
+    pub signature_header: String,
+    pub timestamp_header: String,
+    pub key_id: Option<String>,
+    pub key_id_header: String,
+    pub signature_prefix: String,
+    pub encoding: SignatureEncoding,
+    pub separator: String,
+}
+
+impl HmacSignerConfig {
+    pub fn new(secret: &[u8]) -> Self {
+        Self {
+            secret: secret.to_vec(),
+            signature_header: "X-Signature".to_owned(),
+            timestamp_header: "X-Timestamp".to_owned(),
+            key_id: None,
+            key_id_header: "X-Key-Id".to_owned(),
+            signature_prefix: String::new(),
+            encoding: SignatureEncoding::Hex,
+            separator: "\n".to_owned(),
+        }
+    }
+}
+
+impl fmt::Debug for HmacSignerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacSignerConfig")
+            .field("secret", &"***")
+            .field("signature_header", &self.signature_header)
+            .field("timestamp_header", &self.timestamp_header)
+            .field("key_id", &self.key_id)
+            .field("key_id_header", &self.key_id_header)
+            .field("signature_prefix", &self.signature_prefix)
+            .field("encoding", &self.encoding)
+            .field("separator", &self.separator)
+            .finish()
+    }
+}
+
+///SignatureError: Reasons why a signature verification failed.
+#[derive(Debug, PartialEq)]
+pub enum SignatureError {
+    MissingHeader(String),
+    InvalidTimestamp(String),
+    TimestampOutOfTolerance(u64),
+    Mismatch,
+}
+
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignatureError::MissingHeader(h) => write!(f, "Missing signature header {}", h),
+            SignatureError::InvalidTimestamp(t) => write!(f, "Invalid signature timestamp '{}'", t),
+            SignatureError::TimestampOutOfTolerance(s) => write!(f, "Signature timestamp is {} seconds away from the current time", s),
+            SignatureError::Mismatch => write!(f, "Signature does not match"),
+        }
+    }
+}
+
+impl std::error::Error for SignatureError {}
+
+///HmacSigner: Built-in RequestSigner for HMAC-SHA256 signatures over method, path, timestamp and body.
+/// The same configuration verifies incoming payloads (e.g. webhooks) with verify or verify_headers.
+#[derive(Clone, Debug)]
+pub struct HmacSigner {
+    config: HmacSignerConfig,
+}
+
+impl HmacSigner {
+    pub fn new(config: HmacSignerConfig) -> Self {
+        Self { config }
+    }
+
+    ///Returns the signature header value (prefix included) for the given components.
+    pub fn signature(&self, method: &str, path: &str, timestamp: &str, body: &[u8]) -> String {
+        let raw = self.mac(method, path, timestamp, body).finalize().into_bytes();
+        let encoded = match self.config.encoding {
+            SignatureEncoding::Hex => to_hex(&raw),
+            SignatureEncoding::Base64 => STANDARD.encode(raw),
+        };
+        format!("{}{}", self.config.signature_prefix, encoded)
+    }
+
+    ///Verifies a signature (as found in the signature header) in constant time, and that the timestamp (Unix seconds)
+    /// is within tolerance of the current time.
+    pub fn verify(&self, method: &str, path: &str, timestamp: &str, body: &[u8], signature: &str, tolerance: Duration) -> Result<(), SignatureError> {
+        let ts: u64 = timestamp.trim().parse().map_err(|_| SignatureError::InvalidTimestamp(timestamp.to_owned()))?;
+        let now = unix_now();
+        let diff = now.abs_diff(ts);
+        if diff > tolerance.as_secs() {
+            return Err(SignatureError::TimestampOutOfTolerance(diff));
+        }
+
+        let encoded = signature.trim().strip_prefix(self.config.signature_prefix.as_str()).ok_or(SignatureError::Mismatch)?;
+        let expected = match self.config.encoding {
+            SignatureEncoding::Hex => hex_decode(encoded),
+            SignatureEncoding::Base64 => STANDARD.decode(encoded).ok(),
+        }
+        .ok_or(SignatureError::Mismatch)?;
+        self.mac(method, path, timestamp.trim(), body).verify_slice(&expected).map_err(|_| SignatureError::Mismatch)
+    }
+
+    ///Same as verify reading the signature and timestamp from the configured headers of an incoming request.
+    pub fn verify_headers(&self, method: &str, path: &str, headers: &HttpHeaders, body: &[u8], tolerance: Duration) -> Result<(), SignatureError> {
+        let signature = headers.get(&self.config.signature_header).ok_or_else(|| SignatureError::MissingHeader(self.config.signature_header.clone()))?;
+        let timestamp = headers.get(&self.config.timestamp_header).ok_or_else(|| SignatureError::MissingHeader(self.config.timestamp_header.clone()))?;
+        self.verify(method, path, &timestamp, body, &signature, tolerance)
+    }
+
+    fn mac(&self, method: &str, path: &str, timestamp: &str, body: &[u8]) -> Hmac<Sha256> {
+        let sep = self.config.separator.as_bytes();
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.config.secret).expect("HMAC accepts keys of any size");
+        for part in [method.to_uppercase().as_bytes(), sep, path.as_bytes(), sep, timestamp.as_bytes(), sep, body] {
+            mac.update(part);
+        }
+        mac
+    }
+}
+
+impl RequestSigner for HmacSigner {
+    fn sign(&self, request: &mut Request) -> Result<(), Box<dyn std::error::Error>> {
+        let body: &[u8] = match request.body() {
+            None => b"",
+            Some(b) => match b.as_bytes() {
+                Some(bytes) => bytes,
+                None => return Err(get_error!("sign", "HMAC signature needs the full body. Streaming bodies are not supported").into()),
+            },
+        };
+        let url = request.url();
+        let path = match url.query() {
+            Some(q) => format!("{}?{}", url.path(), q),
+            None => url.path().to_owned(),
+        };
+        let timestamp = unix_now().to_string();
+        let signature = self.signature(request.method().as_str(), &path, &timestamp, body);
+
+        let headers = request.headers_mut();
+        headers.insert(HeaderName::from_bytes(self.config.timestamp_header.as_bytes())?, HeaderValue::from_str(&timestamp)?);
+        headers.insert(HeaderName::from_bytes(self.config.signature_header.as_bytes())?, HeaderValue::from_str(&signature)?);
+        if let Some(key_id) = &self.config.key_id {
+            headers.insert(HeaderName::from_bytes(self.config.key_id_header.as_bytes())?, HeaderValue::from_str(key_id)?);
+        }
+        Ok(())
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+fn hex_decode(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok()).collect()
+}
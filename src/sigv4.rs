@@ -0,0 +1,239 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bt_logger::{get_error, log_warning};
+use hmac::{Hmac, Mac};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{
+    header::{HeaderName, HeaderValue, AUTHORIZATION},
+    Request,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{auth::to_hex, signing::RequestSigner};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+const X_AMZ_DATE: &str = "x-amz-date";
+const X_AMZ_CONTENT_SHA256: &str = "x-amz-content-sha256";
+const X_AMZ_SECURITY_TOKEN: &str = "x-amz-security-token";
+
+///Characters left as is by AWS URI encoding (RFC 3986 unreserved characters).
+const AWS_URI_ENCODE: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'_').remove(b'.').remove(b'~');
+
+///SigV4Config: Credentials and scope used to sign requests with AWS Signature Version 4.
+/// region / service: Signing scope (e.g. "us-east-1" and "s3"). MinIO accepts any region, usually "us-east-1".
+/// access_key_id / secret_access_key: Credentials.
+/// session_token: Optional temporary credentials token (sent and signed as X-Amz-Security-Token).
+/// unsigned_payload: If true the body is not hashed and X-Amz-Content-Sha256: UNSIGNED-PAYLOAD is sent (S3 only).
+#[derive(Clone)]
+pub struct SigV4Config {
+    pub region: String,
+    pub service: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub unsigned_payload: bool,
+}
+
+impl SigV4Config {
+    pub fn new(region: &str, service: &str, access_key_id: &str, secret_access_key: &str) -> Self {
+        Self {
+            region: region.to_owned(),
+            service: service.to_owned(),
+            access_key_id: access_key_id.to_owned(),
+            secret_access_key: secret_access_key.to_owned(),
+            session_token: None,
+            unsigned_payload: false,
+        }
+    }
+}
+
+impl fmt::Debug for SigV4Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigV4Config")
+            .field("region", &self.region)
+            .field("service", &self.service)
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"***")
+            .field("session_token", &self.session_token.as_ref().map(|_| "***"))
+            .field("unsigned_payload", &self.unsigned_payload)
+            .finish()
+    }
+}
+
+///SigV4Signer: RequestSigner for AWS Signature Version 4 (Authorization header).
+/// Signed headers: host, content-type, content-md5 and every x-amz-* header present in the request.
+/// X-Amz-Date is always added. X-Amz-Content-Sha256 is added for the "s3" service or when unsigned_payload is true.
+/// For services other than s3 the path is encoded twice, as required by AWS.
+#[derive(Clone, Debug)]
+pub struct SigV4Signer {
+    config: SigV4Config,
+}
+
+impl SigV4Signer {
+    pub fn new(config: SigV4Config) -> Self {
+        Self { config }
+    }
+
+    ///Signs the request as if it was sent at the given time (useful for tests and pre-signed workflows).
+    pub fn sign_at(&self, request: &mut Request, time: SystemTime) -> Result<(), Box<dyn std::error::Error>> {
+        let (date, amz_date) = format_amz_date(time);
+        let is_s3 = self.config.service == "s3";
+
+        let payload_hash = if self.config.unsigned_payload {
+            UNSIGNED_PAYLOAD.to_owned()
+        } else {
+            match request.body() {
+                None => sha256_hex(b""),
+                Some(body) => match body.as_bytes() {
+                    Some(bytes) => sha256_hex(bytes),
+                    None => {
+                        log_warning!("sign_at", "Streaming body cannot be hashed. Using {}", UNSIGNED_PAYLOAD);
+                        UNSIGNED_PAYLOAD.to_owned()
+                    }
+                },
+            }
+        };
+
+        set_header(request, X_AMZ_DATE, &amz_date)?;
+        if is_s3 || self.config.unsigned_payload {
+            set_header(request, X_AMZ_CONTENT_SHA256, &payload_hash)?;
+        }
+        if let Some(token) = &self.config.session_token {
+            set_header(request, X_AMZ_SECURITY_TOKEN, token)?;
+        }
+
+        let url = request.url();
+        let host = match (url.host_str(), url.port()) {
+            (Some(h), Some(p)) => format!("{}:{}", h, p),
+            (Some(h), None) => h.to_owned(),
+            (None, _) => return Err(get_error!("sign_at", "Cannot sign a request without host").into()),
+        };
+
+        let mut headers: Vec<(String, String)> = vec![("host".to_owned(), host)];
+        for name in request.headers().keys() {
+            let n = name.as_str();
+            if n == "content-type" || n == "content-md5" || n.starts_with("x-amz-") {
+                let values: Vec<String> = request.headers().get_all(name).iter()
+                    .map(|v| collapse_spaces(&String::from_utf8_lossy(v.as_bytes())))
+                    .collect();
+                headers.push((n.to_owned(), values.join(",")));
+            }
+        }
+        headers.sort();
+        let canonical_headers: String = headers.iter().map(|(k, v)| format!("{}:{}\n", k, v)).collect();
+        let signed_headers = headers.iter().map(|(k, _)| k.as_str()).collect::<Vec<&str>>().join(";");
+
+        let canonical_request = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}",
+            request.method().as_str(),
+            canonical_uri(url.path(), is_s3),
+            canonical_query(url.query().unwrap_or_default()),
+            canonical_headers,
+            signed_headers,
+            payload_hash
+        );
+
+        let scope = format!("{}/{}/{}/aws4_request", date, self.config.region, self.config.service);
+        let string_to_sign = format!("{}\n{}\n{}\n{}", ALGORITHM, amz_date, scope, sha256_hex(canonical_request.as_bytes()));
+
+        let k_date = hmac_sha256(format!("AWS4{}", self.config.secret_access_key).as_bytes(), date.as_bytes());
+        let k_region = hmac_sha256(&k_date, self.config.region.as_bytes());
+        let k_service = hmac_sha256(&k_region, self.config.service.as_bytes());
+        let k_signing = hmac_sha256(&k_service, b"aws4_request");
+        let signature = to_hex(&hmac_sha256(&k_signing, string_to_sign.as_bytes()));
+
+        let authorization = format!(
+            "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+            ALGORITHM, self.config.access_key_id, scope, signed_headers, signature
+        );
+        let mut hv = HeaderValue::from_str(&authorization)?;
+        hv.set_sensitive(true);
+        request.headers_mut().insert(AUTHORIZATION, hv);
+        Ok(())
+    }
+}
+
+impl RequestSigner for SigV4Signer {
+    fn sign(&self, request: &mut Request) -> Result<(), Box<dyn std::error::Error>> {
+        self.sign_at(request, SystemTime::now())
+    }
+}
+
+fn set_header(request: &mut Request, name: &'static str, value: &str) -> Result<(), Box<dyn std::error::Error>> {
+    request.headers_mut().insert(HeaderName::from_static(name), HeaderValue::from_str(value)?);
+    Ok(())
+}
+
+///Helper Function: Canonical URI. Url leaves some characters unencoded (e.g. "(", "=" or "@"), so each segment is decoded
+/// and AWS URI encoded: once for s3, twice for the other services.
+fn canonical_uri(path: &str, is_s3: bool) -> String {
+    let path = if path.is_empty() { "/" } else { path };
+    path.split('/')
+        .map(|segment| {
+            let encoded = aws_encode(segment);
+            if is_s3 { encoded } else { utf8_percent_encode(&encoded, AWS_URI_ENCODE).to_string() }
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+///Helper Function: Canonical query string: every name and value AWS URI encoded and sorted by name then value.
+fn canonical_query(query: &str) -> String {
+    let mut pairs: Vec<(String, String)> = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            let (k, v) = p.split_once('=').unwrap_or((p, ""));
+            (aws_encode(k), aws_encode(v))
+        })
+        .collect();
+    pairs.sort();
+    pairs.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<String>>().join("&")
+}
+
+fn aws_encode(raw: &str) -> String {
+    let decoded = percent_decode_str(raw).decode_utf8_lossy();
+    utf8_percent_encode(&decoded, AWS_URI_ENCODE).to_string()
+}
+
+fn collapse_spaces(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+///Helper Function: Returns (YYYYMMDD, YYYYMMDD'T'HHMMSS'Z') in UTC.
+fn format_amz_date(time: SystemTime) -> (String, String) {
+    let secs = time.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+    let date = format!("{:04}{:02}{:02}", year, month, day);
+    let datetime = format!("{}T{:02}{:02}{:02}Z", date, rem / 3600, (rem % 3600) / 60, rem % 60);
+    (date, datetime)
+}
+
+///Helper Function: Converts days since 1970-01-01 to (year, month, day). Howard Hinnant's civil_from_days algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
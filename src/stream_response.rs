@@ -1,9 +1,7 @@
-use std::collections::HashMap;
-
 use bt_logger::{log_error, log_warning};
 use reqwest::Response;
 
-use crate::{convert_headers, HttpResponse};
+use crate::{auth::redact_url, http_headers::HttpHeaders, response_info::ResponseInfo, HttpResponse};
 
 const MAX_NUMBER_ERROR: i8 = 5;
 
@@ -15,6 +13,7 @@ const MAX_NUMBER_ERROR: i8 = 5;
 /// - ini_header: Initial headers of the HTTP response.
 /// - url: URL from which the response was received.
 /// - remote_address: Remote address of the server that sent the response.
+/// - info: Response metadata (ResponseInfo). timings.total is updated each time a chunk is read.
 /// - error_count: Counter for errors encountered during stream reading.
 /// - resp: The actual reqwest::Response object.
 /// 
@@ -22,18 +21,20 @@ const MAX_NUMBER_ERROR: i8 = 5;
 /// - new(http_resp: Response) -> Self: Initializes a new instance of HttpStreamResponse from a reqwest::Response.
 /// - is_error() -> bool: Checks if the HTTP status code indicates an error.
 /// - get_status() -> u16: Returns the HTTP status code as an unsigned 16-bit integer.
-/// - get_ini_header() -> HashMap: Returns a copy of the initial headers.
+/// - get_ini_header() -> HttpHeaders: Returns a copy of the initial headers.
+/// - get_info() -> ResponseInfo: Returns a copy of the response metadata.
 /// - read_stream(&mut self) -> Option: Asynchronously reads and processes the stream from the HTTP response. 
-///     It handles errors by logging them and optionally stopping execution if too many errors occur.
-///     The read_stream method uses asynchronous I/O to read chunks from the response stream.
-///     It processes each chunk individually, converting it to a string if possible.
+///   It handles errors by logging them and optionally stopping execution if too many errors occur.
+///   The read_stream method uses asynchronous I/O to read chunks from the response stream.
+///   It processes each chunk individually, converting it to a string if possible.
 #[derive(Debug)]
 pub struct HttpStreamResponse {
     //ini_status_code: u16,
     ini_status_str: String,
-    ini_header: HashMap<String, String>,
+    ini_header: HttpHeaders,
     url: String,
     remote_address: String,
+    info: ResponseInfo,
     error_count: i8,
     resp: Response,
 }
@@ -51,14 +52,39 @@ impl HttpStreamResponse {
 
         Self { 
             ini_status_str: http_resp.status().canonical_reason().unwrap_or("UNKNOWN ERROR!").to_owned(),
-            ini_header: convert_headers(http_resp.headers()), 
-            url: http_resp.url().to_string(), 
+            ini_header: HttpHeaders::from(http_resp.headers()), 
+            url: redact_url(http_resp.url()), 
             remote_address: ra, 
+            info: ResponseInfo::from_response(&http_resp),
             error_count: 0,
             resp: http_resp,
         }
     }
 
+    ///Stream with the whole body of an already read response as its only chunk (e.g. a middleware short-circuit).
+    pub(crate) fn from_http_response(http_resp: HttpResponse) -> Self {
+        let mut builder = http::Response::builder().status(http_resp.status_code);
+        if let Some(headers) = builder.headers_mut() {
+            *headers = http_resp.header.header_map().clone();
+        }
+        let resp = match builder.body(http_resp.body) {
+            Ok(r) => Response::from(r),
+            Err(e) => {
+                log_error!("from_http_response", "Invalid status code {} ({}). Using 500", http_resp.status_code, e);
+                Response::from(http::Response::builder().status(500).body(String::new()).unwrap_or_default())
+            },
+        };
+        Self {
+            ini_status_str: resp.status().canonical_reason().unwrap_or("UNKNOWN ERROR!").to_owned(),
+            ini_header: http_resp.header,
+            url: http_resp.info.final_url.clone(),
+            remote_address: http_resp.remote_address,
+            info: http_resp.info,
+            error_count: 0,
+            resp,
+        }
+    }
+
     pub fn is_error(&self) -> bool{
         self.resp.status().is_client_error() || self.resp.status().is_server_error()
     }
@@ -67,33 +93,40 @@ impl HttpStreamResponse {
         self.resp.status().as_u16()
     }
 
-    pub fn get_ini_header(&self) -> HashMap<String, String>{
+    pub fn get_ini_header(&self) -> HttpHeaders{
         self.ini_header.clone()
     }
 
+    pub fn get_info(&self) -> ResponseInfo{
+        self.info.clone()
+    }
+
     pub async fn read_stream(&mut self) -> Option<HttpResponse> {
         if self.is_error() { //if response.status().is_client_error() || response.status().is_server_error() {
             log_error!( "read_stream", "ERROR: Failed to read stream response from {}. Status Code: {} ({})", self.url,self.get_status(),self.ini_status_str );
-            return Some(HttpResponse {
+            Some(HttpResponse {
                 status_code: self.get_status(),//response.status().as_u16(),
                 header: self.get_ini_header(), //convert_headers(response.headers()),
                 body: format!( "ERROR: Failed to read stream response from {}. Status: {}.", self.url, self.ini_status_str ),
                 remote_address: self.remote_address.clone(),
-            });
+                info: self.info.clone(),
+            })
         } else {
                 let chunk = self.resp.chunk().await;
+                self.info.finish();
                 match chunk { 
                     Ok(r) => {
                         match r{
                             Some(chunk) => {
-                                return Some(HttpResponse {
+                                Some(HttpResponse {
                                     status_code: self.get_status(),
-                                    header: convert_headers(self.resp.headers()),
-                                    body: (&String::from_utf8_lossy(&chunk)).to_string(),
+                                    header: HttpHeaders::from(self.resp.headers()),
+                                    body: String::from_utf8_lossy(&chunk).to_string(),
                                     remote_address: self.remote_address.clone(),
+                                    info: self.info.clone(),
                                 })
                             },
-                            None => return None, //Stop
+                            None => None, //Stop
                         }
                     },
                     Err(e) => {
@@ -104,12 +137,13 @@ impl HttpStreamResponse {
                         }
 
                         log_error!("read_stream","Error reading streaming from {}. Return Empty body but continue. Error {}", &self.url, e);
-                        return Some(HttpResponse {
+                        Some(HttpResponse {
                             status_code: self.get_status(),
-                            header: convert_headers(self.resp.headers()),
+                            header: HttpHeaders::from(self.resp.headers()),
                             body: "".to_owned(),
                             remote_address: self.remote_address.clone(),
-                        });
+                            info: self.info.clone(),
+                        })
                     },
                 }
             }
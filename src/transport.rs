@@ -0,0 +1,118 @@
+use std::sync::{Arc, OnceLock};
+
+use bt_logger::get_error;
+use reqwest::{redirect, Client, ClientBuilder};
+
+use crate::{
+    client_config::HttpClientConfig, cookie_jar::CookieRouter, dns::TimedResolver, ext_certs::get_local_certificates,
+    request_context::ConnectTimerLayer,
+};
+
+///Transport: Builds the underlying reqwest Client. Verbose connection logging is fixed when a reqwest Client is built,
+/// so there is one Client with it and one without. The second one is only built when needed.
+#[derive(Debug)]
+pub(crate) struct Transport {
+    //Only the transport options (TLS, proxies, DNS, Unix socket and connection) are used
+    config: HttpClientConfig,
+    quiet: OnceLock<Client>,
+    verbose: OnceLock<Client>,
+}
+
+impl Transport {
+    ///Builds the Client of config.connection.verbose. Returns the transport and that Client.
+    pub(crate) fn new(config: &HttpClientConfig) -> Result<(Arc<Self>, Client), Box<dyn std::error::Error>> {
+        let transport = Arc::new(Self {
+            config: HttpClientConfig {
+                use_hickory_dns: config.use_hickory_dns,
+                danger_accept_invalid: config.danger_accept_invalid.clone(),
+                proxies: config.proxies.clone(),
+                ignore_env_proxy: config.ignore_env_proxy,
+                dns: config.dns.clone(),
+                unix_socket: config.unix_socket.clone(),
+                connection: config.connection.clone(),
+                ..Default::default()
+            },
+            quiet: OnceLock::new(),
+            verbose: OnceLock::new(),
+        });
+        let client = transport.client(config.connection.verbose)?;
+        Ok((transport, client))
+    }
+
+    ///Returns the Client with or without verbose connection logging. Clients share nothing but the configuration (each has its pool).
+    pub(crate) fn client(&self, verbose: bool) -> Result<Client, Box<dyn std::error::Error>> {
+        let cell = if verbose { &self.verbose } else { &self.quiet };
+        if let Some(c) = cell.get() {
+            return Ok(c.clone());
+        }
+        let c = self.build(verbose)?;
+        Ok(cell.get_or_init(|| c).clone())
+    }
+
+    fn build(&self, verbose: bool) -> Result<Client, Box<dyn std::error::Error>> {
+        let config = &self.config;
+        let mut cb = Client::builder();
+        if let Some(reqwest_tc) = get_local_certificates(config.danger_accept_invalid.clone()) {
+            cb = cb
+                .use_native_tls()
+                .use_preconfigured_tls(reqwest_tc);
+        }
+        //The Client always routes cookies to the jar of the HttpClient (or session) sending the request. No jar: no cookies.
+        cb = cb.cookie_provider(Arc::new(CookieRouter));
+
+        //Explicit proxies replace the ones from the environment
+        if config.ignore_env_proxy {
+            cb = cb.no_proxy();
+        }
+        for proxy in &config.proxies {
+            cb = cb.proxy(proxy.to_proxy()?);
+        }
+        if let Some(path) = &config.unix_socket {
+            cb = set_unix_socket(cb, path.clone())?;
+        }
+
+        //DNS and connection hooks record the timings of each request (ResponseInfo). Redirects are followed by HttpClient (RedirectPolicy)
+        cb = cb
+            .dns_resolver(Arc::new(TimedResolver::new(config.use_hickory_dns, config.dns.clone())?))
+            .connector_layer(ConnectTimerLayer)
+            .redirect(redirect::Policy::none());
+
+        let connection = &config.connection;
+        if let Some(timeout) = connection.pool_idle_timeout {
+            cb = cb.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = connection.pool_max_idle_per_host {
+            cb = cb.pool_max_idle_per_host(max);
+        }
+        if connection.http1_only {
+            cb = cb.http1_only();
+        }
+        if connection.http2_prior_knowledge {
+            cb = cb.http2_prior_knowledge();
+        }
+        if connection.http2_keep_alive_interval.is_some() {
+            cb = cb.http2_keep_alive_interval(connection.http2_keep_alive_interval).http2_keep_alive_while_idle(true);
+        }
+        cb = cb
+            .tcp_keepalive(connection.tcp_keepalive)
+            .tcp_nodelay(!connection.disable_tcp_nodelay)
+            .http2_adaptive_window(connection.http2_adaptive_window)
+            .connection_verbose(verbose);
+
+        match cb.build() {
+            Ok(c) => Ok(c),
+            Err(e) => Err(get_error!("build", "Failed to build HTTP client. Error: {}", e).into()),
+        }
+    }
+}
+
+///Helper Function: Sends every connection of the client through the Unix domain socket.
+#[cfg(unix)]
+fn set_unix_socket(cb: ClientBuilder, path: std::path::PathBuf) -> Result<ClientBuilder, Box<dyn std::error::Error>> {
+    Ok(cb.unix_socket(path))
+}
+
+#[cfg(not(unix))]
+fn set_unix_socket(_cb: ClientBuilder, path: std::path::PathBuf) -> Result<ClientBuilder, Box<dyn std::error::Error>> {
+    Err(get_error!("set_unix_socket", "Unix domain sockets are not supported on this platform ({})", path.display()).into())
+}
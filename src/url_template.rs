@@ -0,0 +1,276 @@
+use std::collections::HashMap;
+
+use bt_logger::get_error;
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+///Characters left as is by simple expansion (RFC 3986 unreserved characters).
+const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+
+///Characters left as is by reserved ({+var}) and fragment ({#var}) expansion: unreserved plus reserved characters.
+const RESERVED: &AsciiSet = &UNRESERVED
+    .remove(b':').remove(b'/').remove(b'?').remove(b'#').remove(b'[').remove(b']').remove(b'@')
+    .remove(b'!').remove(b'$').remove(b'&').remove(b'\'').remove(b'(').remove(b')')
+    .remove(b'*').remove(b'+').remove(b',').remove(b';').remove(b'=');
+
+///TemplateValue: Value of a URL template variable.
+/// Str: A single value.
+/// List: Several values. {var} gives "a,b"; the explode modifier {?var*} repeats the name: "?var=a&var=b".
+/// Pairs: Ordered name / value pairs. {?var*} gives "?k1=v1&k2=v2"; {var} gives "k1,v1,k2,v2".
+/// Empty lists and pairs are treated as undefined.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TemplateValue {
+    Str(String),
+    List(Vec<String>),
+    Pairs(Vec<(String, String)>),
+}
+
+impl From<&str> for TemplateValue {
+    fn from(value: &str) -> Self {
+        TemplateValue::Str(value.to_owned())
+    }
+}
+
+impl From<String> for TemplateValue {
+    fn from(value: String) -> Self {
+        TemplateValue::Str(value)
+    }
+}
+
+impl From<Vec<String>> for TemplateValue {
+    fn from(values: Vec<String>) -> Self {
+        TemplateValue::List(values)
+    }
+}
+
+impl From<Vec<(String, String)>> for TemplateValue {
+    fn from(pairs: Vec<(String, String)>) -> Self {
+        TemplateValue::Pairs(pairs)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct VarSpec {
+    name: String,
+    explode: bool,
+    prefix: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+enum Part {
+    Literal(String),
+    Expression { operator: Option<char>, vars: Vec<VarSpec> },
+}
+
+///UrlTemplate: URL template (RFC 6570 levels 1 to 3 plus the explode and prefix modifiers).
+/// {var}       Simple expansion, percent-encoded: "a/b c" gives "a%2Fb%20c".
+/// {+var}      Reserved expansion: reserved characters (/ ? # ...) are kept.
+/// {#var}      Fragment: "#" + reserved expansion.
+/// {.var}      Label: ".var".  {/var} Path segments: "/a/b".  {;var} Path parameters: ";var=a".
+/// {?a,b}      Query: "?a=1&b=2".  {&a} Query continuation: "&a=1".
+/// {var*}      Explode lists and pairs.  {var:3} Only the first 3 characters.
+/// Variables of {var}, {+var}, {.var} and {/var} are required (expansion fails if missing). Query, path parameter and fragment
+/// variables are optional and skipped when missing.
+#[derive(Clone, Debug)]
+pub struct UrlTemplate {
+    parts: Vec<Part>,
+}
+
+impl UrlTemplate {
+    ///Parses a template. Returns an error for unclosed braces, unknown operators or invalid variable names.
+    pub fn parse(template: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::parse_template(template, false)
+    }
+
+    ///Parses a URL that may contain literal braces (used by HttpClient::request and request_stream). Only well-formed
+    /// expressions are placeholders: other braces (unclosed, or invalid expressions such as {"a":1}) are kept as literals,
+    /// percent-encoded (%7B and %7D).
+    pub fn parse_lenient(template: &str) -> Self {
+        Self::parse_template(template, true).unwrap_or(Self { parts: vec![Part::Literal(encode_braces(template))] })
+    }
+
+    fn parse_template(template: &str, lenient: bool) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            literal.push_str(&rest[..start]);
+            let expression = rest[start..].find('}').map(|len| (len, parse_expression(&rest[start + 1..start + len], template)));
+            match expression {
+                Some((len, Ok(part))) => {
+                    push_literal(&mut parts, &mut literal, lenient);
+                    parts.push(part);
+                    rest = &rest[start + len + 1..];
+                }
+                Some((_, Err(e))) if !lenient => return Err(e),
+                None if !lenient => return Err(get_error!("parse", "Unclosed placeholder in URL template '{}'", template).into()),
+                _ => {
+                    literal.push('{');
+                    rest = &rest[start + 1..];
+                }
+            }
+        }
+        literal.push_str(rest);
+        push_literal(&mut parts, &mut literal, lenient);
+        Ok(Self { parts })
+    }
+
+    ///Names of the variables used by the template, in order of appearance.
+    pub fn variables(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for part in &self.parts {
+            if let Part::Expression { vars, .. } = part {
+                for v in vars {
+                    if !names.contains(&v.name) {
+                        names.push(v.name.clone());
+                    }
+                }
+            }
+        }
+        names
+    }
+
+    ///Expands the template. Returns an error if a required variable is missing.
+    pub fn expand(&self, values: &HashMap<String, TemplateValue>) -> Result<String, Box<dyn std::error::Error>> {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(l) => out.push_str(l),
+                Part::Expression { operator, vars } => expand_expression(*operator, vars, values, &mut out)?,
+            }
+        }
+        Ok(out)
+    }
+}
+
+///Same as UrlTemplate::parse(template)?.expand(values) with single string values.
+pub fn expand_url(template: &str, values: &HashMap<String, String>) -> Result<String, Box<dyn std::error::Error>> {
+    let values: HashMap<String, TemplateValue> = values.iter().map(|(k, v)| (k.clone(), TemplateValue::from(v.as_str()))).collect();
+    UrlTemplate::parse(template)?.expand(&values)
+}
+
+///Helper Function: Adds the pending literal text. Lenient templates percent-encode its braces.
+fn push_literal(parts: &mut Vec<Part>, literal: &mut String, lenient: bool) {
+    if !literal.is_empty() {
+        let text = std::mem::take(literal);
+        parts.push(Part::Literal(if lenient { encode_braces(&text) } else { text }));
+    }
+}
+
+fn encode_braces(text: &str) -> String {
+    text.replace('{', "%7B").replace('}', "%7D")
+}
+
+fn parse_expression(expression: &str, template: &str) -> Result<Part, Box<dyn std::error::Error>> {
+    let operator = match expression.chars().next() {
+        Some(c @ ('+' | '#' | '.' | '/' | ';' | '?' | '&')) => Some(c),
+        Some(c) if c.is_ascii_alphanumeric() || c == '_' => None,
+        _ => return Err(get_error!("parse_expression", "Invalid expression '{{{}}}' in URL template '{}'", expression, template).into()),
+    };
+    let list = if operator.is_some() { &expression[1..] } else { expression };
+
+    let mut vars = Vec::new();
+    for spec in list.split(',') {
+        let (spec, explode) = match spec.strip_suffix('*') {
+            Some(s) => (s, true),
+            None => (spec, false),
+        };
+        let (name, prefix) = match spec.split_once(':') {
+            Some((n, p)) => match p.parse::<usize>() {
+                Ok(len) if len > 0 && !explode => (n, Some(len)),
+                _ => return Err(get_error!("parse_expression", "Invalid prefix modifier '{}' in URL template '{}'", spec, template).into()),
+            },
+            None => (spec, None),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+            return Err(get_error!("parse_expression", "Invalid variable name '{}' in URL template '{}'", name, template).into());
+        }
+        vars.push(VarSpec { name: name.to_owned(), explode, prefix });
+    }
+    Ok(Part::Expression { operator, vars })
+}
+
+///Helper Function: RFC 6570 expansion of one expression (appendix A algorithm).
+fn expand_expression(operator: Option<char>, vars: &[VarSpec], values: &HashMap<String, TemplateValue>, out: &mut String) -> Result<(), Box<dyn std::error::Error>> {
+    let (first, sep, named, if_empty, allow_reserved) = match operator {
+        None => ("", ",", false, "", false),
+        Some('+') => ("", ",", false, "", true),
+        Some('#') => ("#", ",", false, "", true),
+        Some('.') => (".", ".", false, "", false),
+        Some('/') => ("/", "/", false, "", false),
+        Some(';') => (";", ";", true, "", false),
+        Some('?') => ("?", "&", true, "=", false),
+        _ => ("&", "&", true, "=", false),
+    };
+    let required = matches!(operator, None | Some('+') | Some('.') | Some('/'));
+    let encode = |s: &str| if allow_reserved { encode_reserved(s) } else { utf8_percent_encode(s, UNRESERVED).to_string() };
+
+    let mut expanded: Vec<String> = Vec::new();
+    for var in vars {
+        let value = match values.get(&var.name) {
+            Some(TemplateValue::List(l)) if l.is_empty() => None,
+            Some(TemplateValue::Pairs(p)) if p.is_empty() => None,
+            v => v,
+        };
+        let Some(value) = value else {
+            if required {
+                return Err(get_error!("expand", "Missing value for URL template variable '{}'", var.name).into());
+            }
+            continue;
+        };
+        let item = match value {
+            TemplateValue::Str(s) => {
+                let s = match var.prefix {
+                    Some(len) => s.chars().take(len).collect::<String>(),
+                    None => s.clone(),
+                };
+                match (named, s.is_empty()) {
+                    (true, true) => format!("{}{}", var.name, if_empty),
+                    (true, false) => format!("{}={}", var.name, encode(&s)),
+                    (false, _) => encode(&s),
+                }
+            }
+            TemplateValue::List(list) => {
+                if var.explode {
+                    list.iter()
+                        .map(|v| if named { format!("{}={}", var.name, encode(v)) } else { encode(v) })
+                        .collect::<Vec<String>>()
+                        .join(sep)
+                } else {
+                    let joined = list.iter().map(|v| encode(v)).collect::<Vec<String>>().join(",");
+                    if named { format!("{}={}", var.name, joined) } else { joined }
+                }
+            }
+            TemplateValue::Pairs(pairs) => {
+                if var.explode {
+                    pairs.iter().map(|(k, v)| format!("{}={}", encode(k), encode(v))).collect::<Vec<String>>().join(sep)
+                } else {
+                    let joined = pairs.iter().map(|(k, v)| format!("{},{}", encode(k), encode(v))).collect::<Vec<String>>().join(",");
+                    if named { format!("{}={}", var.name, joined) } else { joined }
+                }
+            }
+        };
+        expanded.push(item);
+    }
+
+    if !expanded.is_empty() {
+        out.push_str(first);
+        out.push_str(&expanded.join(sep));
+    }
+    Ok(())
+}
+
+///Helper Function: Reserved expansion. Keeps reserved characters and already percent-encoded triplets.
+fn encode_reserved(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = String::new();
+    let mut start = 0;
+    for (i, b) in bytes.iter().enumerate() {
+        if *b == b'%' && bytes.get(i + 1).is_some_and(|c| c.is_ascii_hexdigit()) && bytes.get(i + 2).is_some_and(|c| c.is_ascii_hexdigit()) {
+            out.push_str(&utf8_percent_encode(&value[start..i], RESERVED).to_string());
+            out.push('%');
+            start = i + 1;
+        }
+    }
+    out.push_str(&utf8_percent_encode(&value[start..], RESERVED).to_string());
+    out
+}